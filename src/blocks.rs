@@ -0,0 +1,111 @@
+//! Creation of block entities and their physics bodies, shared by `GameState` and the systems
+//! that spawn blocks at runtime through `LazyUpdate`.
+
+use crate::{
+    components::{Block, Grabbable, MaterialKind, PhysicsBody},
+    resources::{MyWorld, BLOCK_GROUP},
+};
+
+use amethyst::{
+    core::{nalgebra::Vector3, Named, Transform},
+    ecs::prelude::{Builder, Entity},
+    renderer::MeshHandle,
+};
+
+use na::Isometry3;
+use ncollide3d::{
+    shape::{Cuboid, ShapeHandle},
+    world::CollisionObjectHandle,
+};
+use nphysics3d::{math::Velocity, object::Material as PhysicsMaterial, volumetric::Volumetric};
+
+pub const COLLIDER_MARGIN: f32 = 0.01;
+const DENSITY: f32 = 1.0;
+
+/// Block sizes and snapped positions are multiples of this.
+pub const GRID_STEP: f32 = 0.25;
+
+/// The default palette, used when `palettes.ron` has none.
+pub const COLORS: [[f32; 4]; 5] = [
+    [0.0, 1.0, 0.0, 1.0],
+    [1.0, 1.0, 0.0, 1.0],
+    [1.0, 0.0, 0.0, 1.0],
+    [1.0, 0.0, 1.0, 1.0],
+    [0.0, 0.0, 1.0, 1.0],
+];
+
+pub struct NewBlock {
+    pub name: String,
+    pub half_extents: Vector3<f32>,
+    pub pos: Isometry3<f32>,
+    pub velocity: Velocity<f32>,
+    pub kind: MaterialKind,
+    pub color: [f32; 4],
+    pub texture: Option<String>,
+    pub grabbable: Grabbable,
+}
+
+pub fn create_block_body(
+    physics_world: &mut MyWorld,
+    half_extents: &Vector3<f32>,
+    pos: Isometry3<f32>,
+    velocity: Velocity<f32>,
+) -> CollisionObjectHandle {
+    let geom = ShapeHandle::new(Cuboid::new(half_extents - Vector3::repeat(COLLIDER_MARGIN)));
+    let inertia = geom.inertia(DENSITY);
+    let center_of_mass = geom.center_of_mass();
+
+    let handle = physics_world.add_rigid_body(pos, inertia, center_of_mass);
+    physics_world
+        .rigid_body_mut(handle)
+        .unwrap()
+        .set_velocity(velocity);
+
+    let collider = physics_world.add_collider(
+        COLLIDER_MARGIN,
+        geom,
+        handle,
+        Isometry3::identity(),
+        PhysicsMaterial::default(),
+    );
+    physics_world.set_membership(collider, BLOCK_GROUP);
+    collider
+}
+
+/// Adds the block's body to `physics_world` and its components to `builder`, which is either
+/// `World::create_entity` or `LazyUpdate::create_entity`.
+pub fn spawn_block<B: Builder>(
+    builder: B,
+    physics_world: &mut MyWorld,
+    mesh: MeshHandle,
+    block: NewBlock,
+) -> Entity {
+    let collider = create_block_body(
+        physics_world,
+        &block.half_extents,
+        block.pos,
+        block.velocity,
+    );
+
+    let mut t = Transform::default();
+    *t.scale_mut() = block.half_extents;
+    *t.translation_mut() = block.pos.translation.vector;
+    *t.rotation_mut() = block.pos.rotation;
+
+    let entity = builder
+        .with(Named::new(block.name))
+        .with(t)
+        .with(mesh)
+        .with(block.grabbable.default_material.clone())
+        .with(PhysicsBody(collider))
+        .with(block.grabbable)
+        .with(block.kind)
+        .with(Block {
+            half_extents: block.half_extents,
+            color: block.color,
+            texture: block.texture,
+        })
+        .build();
+    physics_world.attach(collider, entity);
+    entity
+}
@@ -0,0 +1,17 @@
+use amethyst::{
+    core::nalgebra::Vector3,
+    ecs::{Component, VecStorage},
+};
+
+/// A cuboid block, the render transform is scaled to match `half_extents`.
+pub struct Block {
+    pub half_extents: Vector3<f32>,
+    /// The color its `Grabbable` materials were built from.
+    pub color: [f32; 4],
+    /// One of `TEXTURES`, drawn instead of `color`.
+    pub texture: Option<String>,
+}
+
+impl Component for Block {
+    type Storage = VecStorage<Self>;
+}
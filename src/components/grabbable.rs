@@ -3,11 +3,23 @@ use amethyst::{
     renderer::Material,
 };
 
+#[derive(Clone)]
 pub struct Grabbable {
     pub default_material: Material,
     pub selected_material: Material,
 }
 
+impl Grabbable {
+    /// The material to show, `highlighted` for selected or held blocks.
+    pub fn material(&self, highlighted: bool) -> Material {
+        if highlighted {
+            self.selected_material.clone()
+        } else {
+            self.default_material.clone()
+        }
+    }
+}
+
 impl Component for Grabbable {
     type Storage = VecStorage<Self>;
 }
@@ -0,0 +1,9 @@
+use amethyst::ecs::{Component, NullStorage};
+
+/// Marks the blocks currently carried by the player.
+#[derive(Default)]
+pub struct Held;
+
+impl Component for Held {
+    type Storage = NullStorage<Self>;
+}
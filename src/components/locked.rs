@@ -0,0 +1,9 @@
+use amethyst::ecs::{Component, NullStorage};
+
+/// Marks blocks frozen in place: their body is static and they cannot be grabbed.
+#[derive(Default)]
+pub struct Locked;
+
+impl Component for Locked {
+    type Storage = NullStorage<Self>;
+}
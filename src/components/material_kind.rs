@@ -0,0 +1,59 @@
+use amethyst::ecs::{Component, VecStorage};
+
+/// What a block is made of, decides how it sounds and whether it breaks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MaterialKind {
+    Wood,
+    Stone,
+    Metal,
+    Glass,
+}
+
+impl MaterialKind {
+    pub const ALL: [MaterialKind; 4] = [
+        MaterialKind::Wood,
+        MaterialKind::Stone,
+        MaterialKind::Metal,
+        MaterialKind::Glass,
+    ];
+
+    /// Impulse above which a block shatters, `None` for the ones that never break.
+    pub fn break_impulse(self) -> Option<f32> {
+        match self {
+            MaterialKind::Glass => Some(3.0),
+            _ => None,
+        }
+    }
+
+    /// How rough its surface looks, from mirror-like at 0 to matte at 1.
+    pub fn roughness(self) -> f32 {
+        match self {
+            MaterialKind::Wood => 0.8,
+            MaterialKind::Stone => 0.9,
+            MaterialKind::Metal => 0.3,
+            MaterialKind::Glass => 0.05,
+        }
+    }
+
+    /// The texture blocks made of it start with, if any.
+    pub fn texture(self) -> Option<&'static str> {
+        match self {
+            MaterialKind::Wood => Some("wood"),
+            MaterialKind::Stone => Some("stone"),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MaterialKind::Wood => "wood",
+            MaterialKind::Stone => "stone",
+            MaterialKind::Metal => "metal",
+            MaterialKind::Glass => "glass",
+        }
+    }
+}
+
+impl Component for MaterialKind {
+    type Storage = VecStorage<Self>;
+}
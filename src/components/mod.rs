@@ -1,7 +1,17 @@
+mod block;
 mod camera_self;
 mod grabbable;
+mod held;
+mod locked;
+mod material_kind;
 mod physics_body;
+mod selected;
 
+pub use self::block::Block;
 pub use self::camera_self::CameraSelf;
 pub use self::grabbable::Grabbable;
+pub use self::held::Held;
+pub use self::locked::Locked;
+pub use self::material_kind::MaterialKind;
 pub use self::physics_body::PhysicsBody;
+pub use self::selected::Selected;
@@ -0,0 +1,9 @@
+use amethyst::ecs::{Component, NullStorage};
+
+/// Marks the blocks in the player's selection, which group operations act on.
+#[derive(Default)]
+pub struct Selected;
+
+impl Component for Selected {
+    type Storage = NullStorage<Self>;
+}
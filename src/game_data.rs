@@ -0,0 +1,93 @@
+use crate::resources::Resumed;
+
+use amethyst::{
+    core::{ArcThreadPool, SystemBundle},
+    ecs::prelude::{Dispatcher, DispatcherBuilder, System, World},
+    DataInit, Error, Result, StateEvent, Trans,
+};
+
+pub type GameTrans<'a, 'b> = Trans<CustomGameData<'a, 'b>, StateEvent>;
+
+/// Splits the systems between those that always run, like input, rendering and the UI, and the
+/// gameplay ones, which only run while the game isn't paused or behind a menu.
+pub struct CustomGameData<'a, 'b> {
+    core: Dispatcher<'a, 'b>,
+    running: Dispatcher<'a, 'b>,
+    /// The gameplay systems were left out of a frame since they last ran.
+    frozen: bool,
+}
+
+impl<'a, 'b> CustomGameData<'a, 'b> {
+    /// Runs the systems for a frame, the gameplay ones only if `running`.
+    ///
+    /// The first time the gameplay systems run again after being frozen `Resumed` is set, so
+    /// what was typed and clicked in a menu isn't replayed in the game.
+    pub fn update(&mut self, world: &mut World, running: bool) {
+        if running {
+            world.write_resource::<Resumed>().0 = self.frozen;
+            self.frozen = false;
+            self.running.dispatch(&world.res);
+        } else {
+            self.frozen = true;
+        }
+        self.core.dispatch(&world.res);
+    }
+}
+
+pub struct CustomGameDataBuilder<'a, 'b> {
+    core: DispatcherBuilder<'a, 'b>,
+    running: DispatcherBuilder<'a, 'b>,
+}
+
+impl<'a, 'b> Default for CustomGameDataBuilder<'a, 'b> {
+    fn default() -> Self {
+        CustomGameDataBuilder {
+            core: DispatcherBuilder::new(),
+            running: DispatcherBuilder::new(),
+        }
+    }
+}
+
+impl<'a, 'b> CustomGameDataBuilder<'a, 'b> {
+    pub fn with_base_bundle<B>(mut self, bundle: B) -> Result<Self>
+    where
+        B: SystemBundle<'a, 'b>,
+    {
+        bundle.build(&mut self.core).map_err(Error::Core)?;
+        Ok(self)
+    }
+
+    pub fn with_base<S>(mut self, system: S, name: &str, dependencies: &[&str]) -> Self
+    where
+        for<'c> S: System<'c> + Send + 'a,
+    {
+        self.core.add(system, name, dependencies);
+        self
+    }
+
+    pub fn with_running<S>(mut self, system: S, name: &str, dependencies: &[&str]) -> Self
+    where
+        for<'c> S: System<'c> + Send + 'a,
+    {
+        self.running.add(system, name, dependencies);
+        self
+    }
+}
+
+impl<'a, 'b> DataInit<CustomGameData<'a, 'b>> for CustomGameDataBuilder<'a, 'b> {
+    fn build(self, world: &mut World) -> CustomGameData<'a, 'b> {
+        let pool = world.read_resource::<ArcThreadPool>().clone();
+
+        let mut core = self.core.with_pool(pool.clone()).build();
+        let mut running = self.running.with_pool(pool).build();
+        core.setup(&mut world.res);
+        running.setup(&mut world.res);
+        world.add_resource(Resumed::default());
+
+        CustomGameData {
+            core,
+            running,
+            frozen: false,
+        }
+    }
+}
@@ -1,21 +1,33 @@
-use std::f32;
-
 use crate::{
-    components::{CameraSelf, Grabbable, PhysicsBody},
-    resources::MyWorld,
+    blocks::{spawn_block, NewBlock, COLLIDER_MARGIN, COLORS},
+    components::{CameraSelf, Grabbable, Locked, MaterialKind, PhysicsBody},
+    game_data::{CustomGameData, GameTrans},
+    menu,
+    pause_menu::PausedState,
+    resources::{
+        BlockAssets, Lighting, MyWorld, Paint, Palettes, PhysicsHistory, SavedGame, Settings,
+        Sounds, Surface, PLAYER_GROUP, STATIC_GROUP,
+    },
+    settings_menu::SettingsMenuState,
 };
 
 use amethyst::{
-    assets::{AssetStorage, Loader, ProgressCounter},
+    assets::{AssetStorage, Loader},
+    audio::{output::init_output, Source},
+    config::Config,
+    controls::HideCursor,
     core::{
         nalgebra::{UnitQuaternion, Vector3},
         Transform,
     },
+    ecs::prelude::Builder,
+    input::{is_close_requested, InputEvent, InputHandler},
     prelude::*,
     renderer::{
-        AmbientColor, Camera, DirectionalLight, Light, Material, MaterialDefaults, MeshHandle,
-        PosNormTex, Projection, Rgba, Shape, Texture,
+        Camera, Hidden, Material, MaterialDefaults, MeshHandle, Projection, ScreenDimensions,
+        Texture,
     },
+    shrev::{EventChannel, ReaderId},
     ui::UiCreator,
     utils::application_root_dir,
 };
@@ -28,114 +40,227 @@ use ncollide3d::{
     transformation::ToTriMesh,
 };
 use nphysics3d::{
+    math::Velocity,
     object::{BodyHandle, BodyStatus, Material as PhysicsMaterial},
     volumetric::Volumetric,
 };
-const COLLIDER_MARGIN: f32 = 0.01;
+
+use winit::VirtualKeyCode;
+
 const CAMERA_HEIGHT: f32 = 1.8;
 const INITIAL_CAMERA_X: f32 = 8.0;
 const INITIAL_CAMERA_Z: f32 = 4.0;
-const COLORS: [[f32; 4]; 5] = [
-    [0.0, 1.0, 0.0, 1.0],
-    [1.0, 1.0, 0.0, 1.0],
-    [1.0, 0.0, 0.0, 1.0],
-    [1.0, 0.0, 1.0, 1.0],
-    [0.0, 0.0, 1.0, 1.0],
-];
+const INITIAL_CUBES: usize = 5;
+const BENCHMARK_COLUMN_HEIGHT: usize = 10;
+const BENCHMARK_ORIGIN_X: f32 = -15.0;
+const STACKS_CUBES: usize = 100;
+const AVATAR_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+
+/// Reads the player's settings into `world` and rebinds the actions and axes they list.
+pub fn load_settings(world: &mut World) {
+    let settings = Settings::load_or_create();
+    settings.apply_bindings(
+        &mut world
+            .write_resource::<InputHandler<String, String>>()
+            .bindings,
+    );
+    world.add_resource(settings);
+}
+
+/// The blocks a game starts with.
+#[derive(Clone)]
+pub enum Level {
+    /// A few blocks scattered around.
+    Sandbox,
+    /// Columns of stacked blocks.
+    Stacks,
+    /// The blocks of a saved game.
+    Saved(SavedGame),
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level::Sandbox
+    }
+}
+
+impl Level {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Level::Sandbox => "Sandbox",
+            Level::Stacks => "Stacks",
+            Level::Saved(_) => "Saved game",
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct GameState {
-    pub cube_mesh: Option<MeshHandle>,
+    pub rewind_memory_budget: Option<usize>,
+    pub benchmark_cubes: Option<usize>,
+    pub level: Level,
+    input_reader: Option<ReaderId<InputEvent<String>>>,
 }
 
 impl GameState {
-    fn create_light(&mut self, world: &mut World) {
-        world.add_resource(AmbientColor(Rgba(0.3, 0.3, 0.3, 1.0)));
-        for (dir, pos, color) in [
-            ([-1.0, 0.0, 0.0], [100.0, 0.0, 0.0], 0.08_f32),
-            ([1.0, 0.0, 0.0], [-100.0, 0.0, 0.0], 0.09_f32),
-            ([0.0, 0.0, -1.0], [0.0, 0.0, 100.0], 0.10_f32),
-            ([0.0, 0.0, 1.0], [0.0, 0.0, -100.0], 0.11_f32),
-            ([0.0, -1.0, 0.0], [0.0, 100.0, 0.0], 0.12_f32),
-            ([0.3, -1.0, 0.3], [0.0, 100.0, 10.0], 0.6f32),
-        ]
-        .into_iter()
-        {
-            let mut s = DirectionalLight::default();
-            s.direction = *dir;
-            s.color = Rgba(*color, *color, *color, 1.0);
-            let mut t = Transform::default();
-            *t.translation_mut() = Vector3::new(pos[0], pos[1], pos[2]);
-
-            world
-                .create_entity()
-                .with(t)
-                .with(Light::Directional(s))
-                .build();
-        }
+    fn load_lighting(&mut self, world: &mut World) {
+        let path = format!("{}/resources/lighting.ron", application_root_dir());
+        world.add_resource(Lighting::load(path));
     }
 
-    fn prepare_cubes(&mut self, world: &mut World) {
-        let mesh_storage = world.read_resource();
-        let mut progress = ProgressCounter::default();
-        let loader = world.read_resource::<Loader>();
-        let mesh_data = Shape::Cube.generate::<Vec<PosNormTex>>(None);
-        self.cube_mesh = Some(loader.load_from_data(mesh_data, &mut progress, &mesh_storage));
+    fn prepare_block_assets(&mut self, world: &mut World) {
+        let assets = BlockAssets::new(&world.read_resource(), &world.read_resource());
+        world.add_resource(assets);
     }
 
-    fn create_cube(&mut self, world: &mut World, i: usize, physics_world: &mut MyWorld) {
-        let mut t = Transform::default();
-        *t.scale_mut() = Vector3::new(0.5, 0.5, 0.5);
-        *t.translation_mut() = Vector3::new(
-            (i as f32) * 3.0 - 7.5,
-            (i as f32) * 3.0 + 2.5,
-            3.0 + (-1.0_f32).powf(i as f32) * 0.5,
-        );
-
-        let geom = ShapeHandle::new(Cuboid::new(PhysicsVector3::repeat(0.5 - COLLIDER_MARGIN)));
-        let inertia = geom.inertia(1.0);
-        let center_of_mass = geom.center_of_mass();
-
-        let pos = Isometry3::new(
-            {
-                let translation = t.translation();
-                PhysicsVector3::new(translation[0], translation[1], translation[2])
+    fn create_cube(
+        &mut self,
+        world: &mut World,
+        name: String,
+        pos: Isometry3<f32>,
+        color: [f32; 4],
+        kind: MaterialKind,
+        physics_world: &mut MyWorld,
+    ) {
+        let (grabbable, mesh) = self.block_assets(world, color, kind);
+        spawn_block(
+            world.create_entity(),
+            physics_world,
+            mesh,
+            NewBlock {
+                name,
+                half_extents: Vector3::repeat(0.5),
+                pos,
+                velocity: Velocity::zero(),
+                kind,
+                color,
+                texture: kind.texture().map(str::to_owned),
+                grabbable,
             },
-            Vector3::new(0.9, 0.1, 0.0),
         );
-        let handle = physics_world.add_rigid_body(pos, inertia, center_of_mass);
+    }
 
-        let body_handle = physics_world.add_collider(
-            COLLIDER_MARGIN,
-            geom.clone(),
-            handle,
-            Isometry3::identity(),
-            PhysicsMaterial::default(),
+    /// The materials of a block of `color` and `kind` along with the cube mesh, textures are
+    /// applied by `TextureSystem` afterwards.
+    fn block_assets(
+        &mut self,
+        world: &mut World,
+        color: [f32; 4],
+        kind: MaterialKind,
+    ) -> (Grabbable, MeshHandle) {
+        let mut assets = world.write_resource::<BlockAssets>();
+        let grabbable = assets.grabbable(
+            Surface::color(color),
+            kind,
+            &world.read_resource::<Loader>(),
+            &world.read_resource::<AssetStorage<Texture>>(),
+            &world.read_resource::<MaterialDefaults>().0,
         );
+        (grabbable, assets.cube())
+    }
 
-        let grabbable = {
-            let loader = world.read_resource::<Loader>();
-            let tex_storage = world.read_resource::<AssetStorage<Texture>>();
-            Grabbable {
-                default_material: Material {
-                    albedo: loader.load_from_data(COLORS[i].into(), (), &tex_storage),
-                    ..world.read_resource::<MaterialDefaults>().0.clone()
-                },
-                selected_material: Material {
-                    metallic: loader.load_from_data(COLORS[i].into(), (), &tex_storage),
-                    ..world.read_resource::<MaterialDefaults>().0.clone()
+    fn create_saved_blocks(
+        &mut self,
+        world: &mut World,
+        save: &SavedGame,
+        physics_world: &mut MyWorld,
+    ) {
+        for block in &save.blocks {
+            let (grabbable, mesh) = self.block_assets(world, block.color, block.kind);
+            let [hx, hy, hz] = block.half_extents;
+            let [x, y, z] = block.translation;
+            let [rx, ry, rz] = block.rotation;
+            let mut builder = world.create_entity();
+            if block.locked {
+                builder = builder.with(Locked);
+            }
+            let entity = spawn_block(
+                builder,
+                physics_world,
+                mesh,
+                NewBlock {
+                    name: block.name.clone(),
+                    half_extents: Vector3::new(hx, hy, hz),
+                    pos: Isometry3::new(
+                        PhysicsVector3::new(x, y, z),
+                        PhysicsVector3::new(rx, ry, rz),
+                    ),
+                    velocity: Velocity::zero(),
+                    kind: block.kind,
+                    color: block.color,
+                    texture: block.texture.clone(),
+                    grabbable,
                 },
+            );
+            if block.locked {
+                let collider = world.read_storage::<PhysicsBody>().get(entity).unwrap().0;
+                let rb = physics_world
+                    .collider_body_handle(collider)
+                    .and_then(|bh| physics_world.rigid_body_mut(bh))
+                    .unwrap();
+                rb.set_status(BodyStatus::Static);
             }
+        }
+    }
+
+    /// Colored after the palette the paint tool starts on, `Settings::palette`.
+    fn create_initial_cubes(&mut self, world: &mut World, physics_world: &mut MyWorld) {
+        let colors = {
+            let palettes = world.read_resource::<Palettes>();
+            palettes.palettes[world.read_resource::<Paint>().palette]
+                .colors
+                .clone()
         };
-        world
-            .create_entity()
-            .named(format!("box{}", i))
-            .with(t)
-            .with(self.cube_mesh.clone().unwrap())
-            .with(grabbable.default_material.clone())
-            .with(PhysicsBody(body_handle))
-            .with(grabbable)
-            .build();
+        for i in 0..INITIAL_CUBES {
+            let pos = Isometry3::new(
+                PhysicsVector3::new(
+                    (i as f32) * 3.0 - 7.5,
+                    (i as f32) * 3.0 + 2.5,
+                    3.0 + (-1.0_f32).powf(i as f32) * 0.5,
+                ),
+                PhysicsVector3::new(0.9, 0.1, 0.0),
+            );
+            self.create_cube(
+                world,
+                format!("box{}", i),
+                pos,
+                colors[i % colors.len()],
+                MaterialKind::ALL[i % MaterialKind::ALL.len()],
+                physics_world,
+            );
+        }
+    }
+
+    /// Stacks `count` cubes in columns of `BENCHMARK_COLUMN_HEIGHT`, most of them fall asleep
+    /// once settled which makes it a good scene to measure the transform sync.
+    fn create_benchmark_cubes(
+        &mut self,
+        world: &mut World,
+        count: usize,
+        physics_world: &mut MyWorld,
+    ) {
+        let columns = (count + BENCHMARK_COLUMN_HEIGHT - 1) / BENCHMARK_COLUMN_HEIGHT;
+        let side = (columns as f32).sqrt().ceil() as usize;
+        for i in 0..count {
+            let column = i / BENCHMARK_COLUMN_HEIGHT;
+            let level = i % BENCHMARK_COLUMN_HEIGHT;
+            let pos = Isometry3::new(
+                PhysicsVector3::new(
+                    BENCHMARK_ORIGIN_X - (column % side) as f32 * 1.5,
+                    0.5 + level as f32 * 1.01,
+                    (column / side) as f32 * 1.5 - side as f32 * 0.75,
+                ),
+                na::zero(),
+            );
+            self.create_cube(
+                world,
+                format!("bench{}", i),
+                pos,
+                COLORS[i % COLORS.len()],
+                MaterialKind::Wood,
+                physics_world,
+            );
+        }
     }
 
     fn create_floor(&mut self, world: &mut World, physics_world: &mut MyWorld) {
@@ -145,40 +270,38 @@ impl GameState {
         *t.translation_mut() = Vector3::new(0.0, 0.0, 0.0);
 
         let (plane, color) = {
-            let mesh_storage = world.read_resource();
-            let tex_storage = world.read_resource();
-            let mut progress = ProgressCounter::default();
-            let loader = world.read_resource::<Loader>();
-            let mesh_data = Shape::Cube.generate::<Vec<PosNormTex>>(None);
-            let plane: MeshHandle = loader.load_from_data(mesh_data, &mut progress, &mesh_storage);
+            let mut assets = world.write_resource::<BlockAssets>();
             let color = Material {
-                albedo: loader.load_from_data(
-                    [135.0 / 255.0, 67.0 / 255.0, 23.0 / 255.0, 1.0].into(),
-                    &mut progress,
-                    &tex_storage,
+                albedo: assets.texture(
+                    &Surface::color([135.0 / 255.0, 67.0 / 255.0, 23.0 / 255.0, 1.0]),
+                    &world.read_resource(),
+                    &world.read_resource(),
                 ),
                 ..world.read_resource::<MaterialDefaults>().0.clone()
             };
-            (plane, color)
+            (assets.cube(), color)
         };
 
         let geom = ShapeHandle::new(Cuboid::new(PhysicsVector3::new(1000.0, 0.0, 1000.0)));
 
-        physics_world.add_collider(
+        let floor_handle = physics_world.add_collider(
             COLLIDER_MARGIN,
             geom.clone(),
             BodyHandle::ground(),
             Isometry3::new(PhysicsVector3::new(0.0, 0.0, 0.0), na::zero()),
             PhysicsMaterial::default(),
         );
+        physics_world.set_membership(floor_handle, STATIC_GROUP);
 
-        world
+        let floor = world
             .create_entity()
             .named("floor")
+            .with(PhysicsBody(floor_handle))
             .with(plane)
             .with(color)
             .with(t)
             .build();
+        physics_world.attach(floor_handle, floor);
     }
 
     fn create_self(&mut self, world: &mut World, physics_world: &mut MyWorld) {
@@ -207,13 +330,36 @@ impl GameState {
             Isometry3::identity(),
             PhysicsMaterial::default(),
         );
+        physics_world.set_membership(body_handle, PLAYER_GROUP);
 
-        world
+        // the body seen from the third person and orbit cameras, hidden in first person
+        let mut transform = Transform::default();
+        *transform.translation_mut() = pos.translation.vector;
+        *transform.scale_mut() = aabb.half_extents();
+        let (mesh, material) = {
+            let mut assets = world.write_resource::<BlockAssets>();
+            let material = Material {
+                albedo: assets.texture(
+                    &Surface::color(AVATAR_COLOR),
+                    &world.read_resource(),
+                    &world.read_resource(),
+                ),
+                ..world.read_resource::<MaterialDefaults>().0.clone()
+            };
+            (assets.cube(), material)
+        };
+
+        let player = world
             .create_entity()
             .named("self")
             .with(PhysicsBody(body_handle))
             .with(CameraSelf)
+            .with(transform)
+            .with(mesh)
+            .with(material)
+            .with(Hidden)
             .build();
+        physics_world.attach(body_handle, player);
     }
     fn create_camera(&mut self, world: &mut World) {
         let mut t = Transform::default();
@@ -222,7 +368,9 @@ impl GameState {
             &Vector3::new(1.0, 0.0, 0.0),
             &Vector3::new(0.0, 1.0, 0.0),
         );
-        let c = Camera::from(Projection::perspective(1.3, f32::consts::FRAC_PI_3));
+        let aspect_ratio = world.read_resource::<ScreenDimensions>().aspect_ratio();
+        let fov = world.read_resource::<Settings>().fov_radians();
+        let c = Camera::from(Projection::perspective(aspect_ratio, fov));
         world
             .create_entity()
             .named("camera")
@@ -231,6 +379,33 @@ impl GameState {
             .build();
     }
 
+    fn create_sounds(&mut self, world: &mut World) {
+        // does nothing but log an error when there's no audio device, `SoundSystem` copes
+        init_output(&mut world.res);
+        let sounds = Sounds::load(
+            &world.read_resource::<Loader>(),
+            &world.read_resource::<AssetStorage<Source>>(),
+        );
+        world.add_resource(sounds);
+    }
+
+    fn load_palettes(&mut self, world: &mut World) {
+        let path = format!("{}/resources/palettes.ron", application_root_dir());
+        let mut palettes = Palettes::load(path);
+        if palettes.palettes.iter().all(|p| p.colors.is_empty()) {
+            warn!("palettes.ron has no colors, using the default palette");
+            palettes = Palettes::default();
+        }
+        palettes.palettes.retain(|p| !p.colors.is_empty());
+        let name = world.read_resource::<Settings>().palette.clone();
+        let palette = palettes.find(&name).unwrap_or_else(|| {
+            warn!("there is no palette called {}, using the first one", name);
+            0
+        });
+        world.write_resource::<Paint>().palette = palette;
+        world.add_resource(palettes);
+    }
+
     fn create_center(&mut self, world: &mut World) {
         world.exec(|mut creator: UiCreator| {
             let app_root = application_root_dir();
@@ -238,27 +413,95 @@ impl GameState {
         });
     }
 }
-impl SimpleState for GameState {
-    fn on_start(&mut self, data: StateData<GameData>) {
+impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for GameState {
+    fn on_start(&mut self, data: StateData<CustomGameData>) {
         data.world.register::<PhysicsBody>();
         data.world.register::<CameraSelf>();
         let mut physics_world = MyWorld::default();
-        self.create_light(data.world);
+        load_settings(data.world);
+        self.load_lighting(data.world);
+        self.prepare_block_assets(data.world);
         self.create_floor(data.world, &mut physics_world);
-        self.prepare_cubes(data.world);
-        for i in 0..5 {
-            self.create_cube(data.world, i, &mut physics_world);
+        self.load_palettes(data.world);
+        match self.level.clone() {
+            Level::Sandbox => self.create_initial_cubes(data.world, &mut physics_world),
+            Level::Stacks => {
+                self.create_benchmark_cubes(data.world, STACKS_CUBES, &mut physics_world)
+            }
+            Level::Saved(save) => self.create_saved_blocks(data.world, &save, &mut physics_world),
+        }
+        if let Some(count) = self.benchmark_cubes {
+            self.create_benchmark_cubes(data.world, count, &mut physics_world);
         }
         physics_world.step();
         physics_world.set_gravity(-PhysicsVector3::y() * 9.81);
         self.create_self(data.world, &mut physics_world);
         self.create_camera(data.world);
         self.create_center(data.world);
+        self.create_sounds(data.world);
 
         // let mut testbed = nphysics_testbed3d::Testbed::new(physics_world.inner);
         // testbed.look_at(Point3::new(-4.0, 1.0, -4.0), Point3::new(0.0, 1.0, 0.0));
         // testbed.run();
 
         data.world.add_resource(physics_world);
+        if let Some(budget) = self.rewind_memory_budget {
+            data.world
+                .add_resource(PhysicsHistory::with_memory_budget(budget));
+        }
+        data.world.write_resource::<HideCursor>().hide = true;
+        self.input_reader = Some(
+            data.world
+                .write_resource::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+
+    fn on_resume(&mut self, data: StateData<CustomGameData>) {
+        // keys pressed in the menu aren't meant for the game
+        if let Some(ref mut reader) = self.input_reader {
+            let channel = data
+                .world
+                .read_resource::<EventChannel<InputEvent<String>>>();
+            for _ in channel.read(reader) {}
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<CustomGameData>,
+        event: StateEvent,
+    ) -> GameTrans<'a, 'b> {
+        match event {
+            StateEvent::Window(ref event) if is_close_requested(event) => Trans::Quit,
+            StateEvent::Window(ref event)
+                if menu::is_key_pressed(data.world, event, VirtualKeyCode::Escape) =>
+            {
+                Trans::Push(Box::new(PausedState::default()))
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(&mut self, data: StateData<CustomGameData>) -> GameTrans<'a, 'b> {
+        data.data.update(data.world, true);
+        let channel = data
+            .world
+            .read_resource::<EventChannel<InputEvent<String>>>();
+        let open_settings = channel
+            .read(
+                self.input_reader
+                    .as_mut()
+                    .expect("`GameState::on_start` was not called before `GameState::update`"),
+            )
+            .any(|event| match *event {
+                InputEvent::ActionPressed(ref action) => action == "open_settings",
+                _ => false,
+            });
+        if open_settings {
+            Trans::Push(Box::new(SettingsMenuState::default()))
+        } else {
+            Trans::None
+        }
     }
 }
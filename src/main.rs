@@ -1,31 +1,75 @@
 extern crate amethyst;
+extern crate dirs;
+#[macro_use]
+extern crate log;
 extern crate nalgebra as na;
 extern crate ncollide3d;
 extern crate nphysics3d;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 // extern crate nphysics_testbed3d;
 extern crate specs;
 extern crate winit;
 
+mod blocks;
 mod components;
+mod game_data;
 mod game_state;
+mod main_menu;
+mod menu;
+mod patterns;
+mod pause_menu;
 mod resources;
+mod settings_menu;
 mod systems;
 
+use std::env;
+
 use crate::{
+    game_data::CustomGameDataBuilder,
     game_state::GameState,
-    systems::{PhysicsSystem, MoveSystem, RotationSystem, TranslationSystem},
+    main_menu::MainMenuState,
+    resources::Sounds,
+    systems::{
+        BreakageSystem, CameraSystem, CursorSystem, DayNightSystem, DuplicateSystem, HudSystem,
+        LightingSystem, MoveSystem, PaintSystem, PatternSystem, PhysicsControlSystem,
+        PhysicsSystem, PickSystem, ProjectionSystem, PulseSystem, ResizeSystem, RotationSystem,
+        SelectionActionsSystem, SelectionSystem, SoundSystem, StressViewSystem, TextureSystem,
+        TranslationSystem,
+    },
 };
 
 use amethyst::{
+    audio::AudioBundle,
     controls::{CursorHideSystem, MouseFocusUpdateSystem},
     core::transform::TransformBundle,
     input::InputBundle,
     prelude::*,
-    renderer::{DisplayConfig, DrawShaded, Pipeline, PosNormTex, RenderBundle, Stage},
+    renderer::{DisplayConfig, DrawShaded, DrawSkybox, Pipeline, PosNormTex, RenderBundle, Stage},
     ui::{DrawUi, UiBundle},
     utils::application_root_dir,
 };
 
+fn parse_args() -> GameState {
+    let mut state = GameState::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rewind-budget-mb" => match args.next().and_then(|x| x.parse::<usize>().ok()) {
+                Some(mb) => state.rewind_memory_budget = Some(mb * 1024 * 1024),
+                None => warn!("--rewind-budget-mb expects a number of megabytes"),
+            },
+            "--bench" => match args.next().and_then(|x| x.parse::<usize>().ok()) {
+                Some(count) => state.benchmark_cubes = Some(count),
+                None => warn!("--bench expects a number of cubes"),
+            },
+            _ => warn!("ignoring unknown argument {}", arg),
+        }
+    }
+    state
+}
+
 fn main() -> amethyst::Result<()> {
     amethyst::start_logger(Default::default());
 
@@ -38,35 +82,115 @@ fn main() -> amethyst::Result<()> {
     let pipe = Pipeline::build().with_stage(
         Stage::with_backbuffer()
             .clear_target([30.0 / 255.0, 144.0 / 255.0, 1.0, 1.0], 1.0)
+            .with_pass(DrawSkybox::new())
             .with_pass(DrawShaded::<PosNormTex>::new())
             .with_pass(DrawUi::new()),
     );
 
-    let game_data = GameDataBuilder::default()
-        .with_bundle(
+    let game_data = CustomGameDataBuilder::default()
+        .with_base_bundle(
             InputBundle::<String, String>::new().with_bindings_from_file(&key_bindings_path)?,
         )?
-        .with(RotationSystem::default(), "rotation_system", &[])
-        .with(TranslationSystem::default(), "translation_system", &[])
-        .with_bundle(TransformBundle::new().with_dep(&[]))?
-        .with_bundle(UiBundle::<String, String>::new())?
-        .with_bundle(
+        .with_base_bundle(TransformBundle::new().with_dep(&[]))?
+        .with_base_bundle(UiBundle::<String, String>::new())?
+        .with_base_bundle(
             RenderBundle::new(pipe, Some(DisplayConfig::load(&display_config_path)))
                 .with_sprite_sheet_processor(),
         )?
-        .with(MouseFocusUpdateSystem::new(), "mouse_focus", &[])
-        .with(CursorHideSystem::new(), "cursor_hide", &["mouse_focus"])
-        .with(
+        .with_base_bundle(AudioBundle::new(|_: &mut Sounds| None))?
+        .with_base(ProjectionSystem::default(), "projection_system", &[])
+        .with_base(MouseFocusUpdateSystem::new(), "mouse_focus", &[])
+        .with_base(CursorHideSystem::new(), "cursor_hide", &["mouse_focus"])
+        .with_running(RotationSystem::default(), "rotation_system", &[])
+        .with_running(TranslationSystem::default(), "translation_system", &[])
+        .with_running(
+            CameraSystem::default(),
+            "camera_system",
+            &["rotation_system", "translation_system"],
+        )
+        .with_running(PickSystem::default(), "pick_system", &["camera_system"])
+        .with_running(
+            SelectionSystem::default(),
+            "selection_system",
+            &["pick_system"],
+        )
+        .with_running(
+            SelectionActionsSystem::default(),
+            "selection_actions_system",
+            &["selection_system"],
+        )
+        .with_running(CursorSystem::default(), "cursor_system", &[])
+        .with_running(
             MoveSystem::default(),
             "move_system",
-            &["rotation_system", "translation_system"],
+            &[
+                "cursor_system",
+                "camera_system",
+                "pick_system",
+                "selection_actions_system",
+            ],
+        )
+        .with_running(
+            DuplicateSystem::default(),
+            "duplicate_system",
+            &["move_system"],
         )
-        .with(
+        .with_running(ResizeSystem::default(), "resize_system", &["move_system"])
+        .with_running(PaintSystem::default(), "paint_system", &["pick_system"])
+        .with_running(DayNightSystem::default(), "day_night_system", &[])
+        .with_running(
+            LightingSystem::default(),
+            "lighting_system",
+            &["pick_system", "day_night_system"],
+        )
+        .with_running(
+            PatternSystem::default(),
+            "pattern_system",
+            &["paint_system", "selection_actions_system"],
+        )
+        .with_running(
+            TextureSystem::default(),
+            "texture_system",
+            &["pattern_system", "resize_system"],
+        )
+        .with_running(
+            PulseSystem::default(),
+            "pulse_system",
+            &["texture_system", "move_system"],
+        )
+        .with_running(
+            PhysicsControlSystem::default(),
+            "physics_control_system",
+            &[],
+        )
+        .with_running(
             PhysicsSystem::default(),
             "physics_system",
-            &["move_system"],
+            &["move_system", "physics_control_system"],
+        )
+        .with_running(HudSystem::default(), "hud_system", &["physics_system"])
+        .with_running(
+            StressViewSystem::default(),
+            "stress_view_system",
+            &["physics_system"],
+        )
+        .with_running(
+            BreakageSystem::default(),
+            "breakage_system",
+            &["physics_system"],
+        )
+        .with_running(
+            SoundSystem::default(),
+            "sound_system",
+            &["physics_system", "move_system"],
         );
-    let mut game = Application::new("./", GameState::default(), game_data)?;
+    let game = parse_args();
+    // a benchmark goes straight into the game, so runs can be timed from the start
+    let mut game = if game.benchmark_cubes.is_some() {
+        Application::new("./", game, game_data)?
+    } else {
+        Application::new("./", MainMenuState::new(game), game_data)?
+    };
 
     game.run();
 
@@ -0,0 +1,119 @@
+use crate::{
+    game_data::{CustomGameData, GameTrans},
+    game_state::{load_settings, GameState, Level},
+    menu,
+    resources::SavedGame,
+    settings_menu::SettingsMenuState,
+};
+
+use amethyst::{controls::HideCursor, ecs::Entity, input::is_close_requested, prelude::*};
+
+/// The levels `level_next` goes through.
+const LEVELS: [Level; 2] = [Level::Sandbox, Level::Stacks];
+
+/// The first screen, built from `resources/main_menu.ron`: starts a new game on the chosen level,
+/// loads the saved one, opens the settings or quits.
+pub struct MainMenuState {
+    /// The game to start, with the options given on the command line.
+    game: Option<GameState>,
+    menu: Option<Entity>,
+    /// Index in `LEVELS`.
+    level: usize,
+    /// Why the saved game couldn't be loaded, if it couldn't.
+    status: String,
+}
+
+impl MainMenuState {
+    pub fn new(game: GameState) -> Self {
+        MainMenuState {
+            game: Some(game),
+            menu: None,
+            level: 0,
+            status: String::new(),
+        }
+    }
+
+    fn open(&mut self, world: &mut World) {
+        world.write_resource::<HideCursor>().hide = false;
+        self.menu = Some(menu::create(world, "main_menu.ron"));
+    }
+
+    fn close(&mut self, world: &mut World) {
+        if let Some(menu) = self.menu.take() {
+            menu::delete(world, menu);
+        }
+    }
+
+    fn start<'a, 'b>(&mut self, level: Level) -> GameTrans<'a, 'b> {
+        let mut game = self.game.take().unwrap_or_default();
+        game.level = level;
+        Trans::Switch(Box::new(game))
+    }
+
+    fn load_save<'a, 'b>(&mut self) -> GameTrans<'a, 'b> {
+        match SavedGame::load_saved() {
+            Ok(save) => self.start(Level::Saved(save)),
+            Err(e) => {
+                warn!("couldn't load the saved game: {}", e);
+                self.status = "There is no saved game to load".to_owned();
+                Trans::None
+            }
+        }
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for MainMenuState {
+    fn on_start(&mut self, data: StateData<CustomGameData>) {
+        load_settings(data.world);
+        self.open(data.world);
+    }
+
+    fn on_pause(&mut self, data: StateData<CustomGameData>) {
+        self.close(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<CustomGameData>) {
+        self.open(data.world);
+    }
+
+    fn on_stop(&mut self, data: StateData<CustomGameData>) {
+        self.close(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<CustomGameData>,
+        event: StateEvent,
+    ) -> GameTrans<'a, 'b> {
+        let button = match event {
+            StateEvent::Window(ref event) if is_close_requested(event) => return Trans::Quit,
+            ref event => menu::clicked(data.world, event),
+        };
+        match button.as_ref().map(String::as_str) {
+            Some("new_game") => {
+                let level = LEVELS[self.level].clone();
+                self.start(level)
+            }
+            Some("level_next") => {
+                self.level = (self.level + 1) % LEVELS.len();
+                Trans::None
+            }
+            Some("load_save") => self.load_save(),
+            Some("settings") => Trans::Push(Box::new(SettingsMenuState::default())),
+            Some("quit") => Trans::Quit,
+            _ => Trans::None,
+        }
+    }
+
+    fn update(&mut self, data: StateData<CustomGameData>) -> GameTrans<'a, 'b> {
+        data.data.update(data.world, false);
+        menu::set_texts(
+            data.world,
+            &[
+                ("level_value", LEVELS[self.level].name().to_owned()),
+                ("main_status", self.status.clone()),
+            ],
+        );
+        Trans::None
+    }
+}
@@ -0,0 +1,71 @@
+//! Helpers shared by the menu states, which are built from RON files with `UiCreator`.
+
+use amethyst::{
+    core::ParentHierarchy,
+    ecs::{Entity, Join, WriteStorage},
+    input::{is_key_down, InputHandler},
+    prelude::*,
+    ui::{UiCreator, UiEvent, UiEventType, UiFinder, UiText, UiTransform},
+    utils::application_root_dir,
+};
+
+use winit::{Event, VirtualKeyCode};
+
+/// Builds the menu described in `resources/<file>`, returning its root entity.
+pub fn create(world: &mut World, file: &str) -> Entity {
+    world.exec(|mut creator: UiCreator| {
+        let app_root = application_root_dir();
+        creator.create(format!("{}/resources/{}", app_root, file), ())
+    })
+}
+
+/// Deletes a menu built by `create` along with everything in it.
+pub fn delete(world: &mut World, menu: Entity) {
+    let mut menu_entities: Vec<Entity> = {
+        let children = world.read_resource::<ParentHierarchy>().all_children(menu);
+        (&*world.entities(), &children)
+            .join()
+            .map(|(e, _)| e)
+            .collect()
+    };
+    menu_entities.push(menu);
+    world.delete_entities(&menu_entities).unwrap();
+}
+
+/// The id of the button `event` is a click on, if it is one.
+pub fn clicked(world: &World, event: &StateEvent) -> Option<String> {
+    match *event {
+        StateEvent::Ui(UiEvent {
+            event_type: UiEventType::Click,
+            target,
+        }) => world
+            .read_storage::<UiTransform>()
+            .get(target)
+            .map(|t| t.id.clone()),
+        _ => None,
+    }
+}
+
+/// Whether `event` presses `key`, leaving out the presses repeated while it is held.
+///
+/// States handle an event before `InputHandler` sees it, so it only says `key` is down when an
+/// earlier event pressed it.
+pub fn is_key_pressed(world: &World, event: &Event, key: VirtualKeyCode) -> bool {
+    is_key_down(event, key)
+        && !world
+            .read_resource::<InputHandler<String, String>>()
+            .key_is_down(key)
+}
+
+/// Writes each text into the element with its id, once the menu has loaded.
+pub fn set_texts(world: &mut World, values: &[(&str, String)]) {
+    world.exec(|(finder, mut texts): (UiFinder, WriteStorage<UiText>)| {
+        for (id, value) in values {
+            if let Some(text) = finder.find(id).and_then(|e| texts.get_mut(e)) {
+                if text.text != *value {
+                    text.text = value.clone();
+                }
+            }
+        }
+    });
+}
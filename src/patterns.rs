@@ -0,0 +1,58 @@
+//! Patterns drawn on blocks on top of their color, so blocks can be told apart without relying
+//! on color vision.
+
+use crate::resources::Palettes;
+
+use amethyst::renderer::{TextureData, TextureMetadata};
+
+pub const PATTERNS: usize = 8;
+const SIZE: usize = 16;
+/// How much darker the pattern's lines are than the block's color.
+const SHADE: f32 = 0.45;
+
+fn is_line(pattern: usize, x: usize, y: usize) -> bool {
+    match pattern {
+        0 => false,
+        1 => y % 8 < 2,
+        2 => x % 8 < 2,
+        3 => (x / 4 + y / 4) % 2 == 0,
+        4 => {
+            // the middle four pixels of every eight
+            let is_middle = |v: usize| v % 8 / 2 == 1 || v % 8 / 2 == 2;
+            is_middle(x) && is_middle(y)
+        }
+        5 => (x + y) % 8 < 2,
+        6 => x % 8 < 2 || y % 8 < 2,
+        _ => x < 2 || y < 2 || x >= SIZE - 2 || y >= SIZE - 2,
+    }
+}
+
+/// The pattern for `color`: its position in the first palette that has it, so every color of a
+/// palette gets a different one, or a hash of the color otherwise.
+pub fn pattern_for(color: [f32; 4], palettes: &Palettes) -> usize {
+    palettes
+        .palettes
+        .iter()
+        .find_map(|p| p.colors.iter().position(|c| *c == color))
+        .unwrap_or_else(|| {
+            color.iter().fold(0usize, |h, c| {
+                h.wrapping_mul(31).wrapping_add(c.to_bits() as usize)
+            })
+        })
+        % PATTERNS
+}
+
+pub fn pattern_texture(color: [f32; 4], pattern: usize) -> TextureData {
+    let mut pixels = Vec::with_capacity(SIZE * SIZE * 4);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let shade = if is_line(pattern, x, y) { SHADE } else { 1.0 };
+            pixels.extend(color[..3].iter().map(|c| (c * shade * 255.0) as u8));
+            pixels.push((color[3] * 255.0) as u8);
+        }
+    }
+    TextureData::U8(
+        pixels,
+        TextureMetadata::srgb().with_size(SIZE as u16, SIZE as u16),
+    )
+}
@@ -0,0 +1,96 @@
+use crate::{
+    game_data::{CustomGameData, GameTrans},
+    menu,
+    resources::SavedGame,
+    settings_menu::SettingsMenuState,
+};
+
+use amethyst::{controls::HideCursor, ecs::Entity, input::is_close_requested, prelude::*};
+
+use winit::VirtualKeyCode;
+
+/// Pushed over `GameState` with `Escape`, built from `resources/pause_menu.ron`.
+///
+/// The gameplay systems, physics included, don't run while it is open; resuming pops back into
+/// the same game. The cursor is released so its buttons can be clicked.
+#[derive(Default)]
+pub struct PausedState {
+    menu: Option<Entity>,
+    /// What the last press of `Save` did.
+    status: String,
+}
+
+impl PausedState {
+    fn open(&mut self, world: &mut World) {
+        self.menu = Some(menu::create(world, "pause_menu.ron"));
+    }
+
+    fn close(&mut self, world: &mut World) {
+        if let Some(menu) = self.menu.take() {
+            menu::delete(world, menu);
+        }
+    }
+
+    fn save(&mut self, world: &World) {
+        self.status = match SavedGame::capture(world).save() {
+            Ok(()) => "Saved".to_owned(),
+            Err(e) => {
+                error!("couldn't save the game: {}", e);
+                "Couldn't save the game".to_owned()
+            }
+        };
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for PausedState {
+    fn on_start(&mut self, data: StateData<CustomGameData>) {
+        data.world.write_resource::<HideCursor>().hide = false;
+        self.open(data.world);
+    }
+
+    fn on_pause(&mut self, data: StateData<CustomGameData>) {
+        // the settings are drawn on their own, with no buttons below to click by mistake
+        self.close(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<CustomGameData>) {
+        self.open(data.world);
+    }
+
+    fn on_stop(&mut self, data: StateData<CustomGameData>) {
+        self.close(data.world);
+        data.world.write_resource::<HideCursor>().hide = true;
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<CustomGameData>,
+        event: StateEvent,
+    ) -> GameTrans<'a, 'b> {
+        let button = match event {
+            StateEvent::Window(ref event) if is_close_requested(event) => return Trans::Quit,
+            StateEvent::Window(ref event)
+                if menu::is_key_pressed(data.world, event, VirtualKeyCode::Escape) =>
+            {
+                Some("resume".to_owned())
+            }
+            ref event => menu::clicked(data.world, event),
+        };
+        match button.as_ref().map(String::as_str) {
+            Some("resume") => Trans::Pop,
+            Some("settings") => Trans::Push(Box::new(SettingsMenuState::default())),
+            Some("save") => {
+                self.save(data.world);
+                Trans::None
+            }
+            Some("quit") => Trans::Quit,
+            _ => Trans::None,
+        }
+    }
+
+    fn update(&mut self, data: StateData<CustomGameData>) -> GameTrans<'a, 'b> {
+        data.data.update(data.world, false);
+        menu::set_texts(data.world, &[("pause_status", self.status.clone())]);
+        Trans::None
+    }
+}
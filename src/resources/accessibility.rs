@@ -0,0 +1,16 @@
+/// Cues that don't rely on telling colors apart.
+pub struct Accessibility {
+    /// Draws a pattern on every block, a different one for each color of a palette.
+    pub patterns: bool,
+    /// Pulses the brightness of the pointed and the selected blocks.
+    pub pulse: bool,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Accessibility {
+            patterns: false,
+            pulse: true,
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use crate::{
+    components::{Grabbable, MaterialKind},
+    patterns::pattern_texture,
+};
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    core::nalgebra::{Vector2, Vector3},
+    renderer::{
+        FilterMethod, Material, Mesh, MeshHandle, PngFormat, PosNormTex, SamplerInfo, Shape,
+        Texture, TextureHandle, TextureMetadata, WrapMode,
+    },
+};
+
+/// The textures blocks can use, files in `resources/textures/` without their extension.
+pub const TEXTURES: [&str; 3] = ["wood", "brick", "stone"];
+/// How many times a texture repeats along one unit of a face.
+const REPEATS_PER_UNIT: f32 = 1.0;
+
+/// A face of the unit cube: its normal and the axes its texture's u and v follow, chosen so
+/// `u x v = normal` and its triangles wind counter clockwise seen from outside.
+const FACES: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+    ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+    ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+    ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+    ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+];
+
+/// A cube from -1 to 1, like `Shape::Cube`, with texture coordinates scaled by the size of each
+/// face once the render transform scales it to `half_extents`, so textures tile instead of
+/// stretching.
+fn box_vertices(half_extents: &Vector3<f32>) -> Vec<PosNormTex> {
+    let mut vertices = Vec::with_capacity(36);
+    for (normal, u, v) in FACES.iter() {
+        let normal = Vector3::from_row_slice(normal);
+        let u = Vector3::from_row_slice(u);
+        let v = Vector3::from_row_slice(v);
+        let size_u = u.abs().dot(half_extents) * 2.0 * REPEATS_PER_UNIT;
+        let size_v = v.abs().dot(half_extents) * 2.0 * REPEATS_PER_UNIT;
+        let corner = |a: f32, b: f32| PosNormTex {
+            position: normal + u * a + v * b,
+            normal,
+            tex_coord: Vector2::new((a + 1.0) / 2.0 * size_u, (b + 1.0) / 2.0 * size_v),
+        };
+        // two triangles per face
+        for (a, b) in [
+            (-1.0, -1.0),
+            (1.0, -1.0),
+            (1.0, 1.0),
+            (-1.0, -1.0),
+            (1.0, 1.0),
+            (-1.0, 1.0),
+        ]
+        .iter()
+        {
+            vertices.push(corner(*a, *b));
+        }
+    }
+    vertices
+}
+
+/// What covers a block's faces. Colors are kept with 8 bits per channel, so surfaces can be
+/// hashed and colors too close to tell apart share their texture and material.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Surface {
+    Color([u8; 4]),
+    /// A color with one of the `patterns`.
+    Pattern([u8; 4], usize),
+    /// One of `TEXTURES`.
+    Texture(String),
+}
+
+fn quantize(color: [f32; 4]) -> [u8; 4] {
+    let channel = |c: f32| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+    [
+        channel(color[0]),
+        channel(color[1]),
+        channel(color[2]),
+        channel(color[3]),
+    ]
+}
+
+fn dequantize(color: [u8; 4]) -> [f32; 4] {
+    let channel = |c: u8| f32::from(c) / 255.0;
+    [
+        channel(color[0]),
+        channel(color[1]),
+        channel(color[2]),
+        channel(color[3]),
+    ]
+}
+
+impl Surface {
+    pub fn color(color: [f32; 4]) -> Self {
+        Surface::Color(quantize(color))
+    }
+
+    pub fn pattern(color: [f32; 4], pattern: usize) -> Self {
+        Surface::Pattern(quantize(color), pattern)
+    }
+}
+
+/// Meshes, textures and materials for blocks, created on first use and shared by every block
+/// that looks the same, so spawning many blocks doesn't upload the same texture many times.
+pub struct BlockAssets {
+    cube: MeshHandle,
+    /// Cubes whose texture coordinates tile textures over faces of the given half extents.
+    tiled_cubes: HashMap<[u32; 3], MeshHandle>,
+    textures: HashMap<Surface, TextureHandle>,
+    roughness: HashMap<MaterialKind, TextureHandle>,
+    /// Both the default and the selected material of every surface and kind.
+    grabbables: HashMap<(Surface, MaterialKind), Grabbable>,
+}
+
+impl BlockAssets {
+    pub fn new(loader: &Loader, mesh_storage: &AssetStorage<Mesh>) -> Self {
+        BlockAssets {
+            cube: loader.load_from_data(
+                Shape::Cube.generate::<Vec<PosNormTex>>(None),
+                (),
+                mesh_storage,
+            ),
+            tiled_cubes: HashMap::new(),
+            textures: HashMap::new(),
+            roughness: HashMap::new(),
+            grabbables: HashMap::new(),
+        }
+    }
+
+    /// The cube from -1 to 1 untextured blocks are drawn with, scaled by their transform.
+    pub fn cube(&self) -> MeshHandle {
+        self.cube.clone()
+    }
+
+    pub fn tiled_cube(
+        &mut self,
+        half_extents: &Vector3<f32>,
+        loader: &Loader,
+        mesh_storage: &AssetStorage<Mesh>,
+    ) -> MeshHandle {
+        let key = [
+            half_extents.x.to_bits(),
+            half_extents.y.to_bits(),
+            half_extents.z.to_bits(),
+        ];
+        self.tiled_cubes
+            .entry(key)
+            .or_insert_with(|| {
+                loader.load_from_data(box_vertices(half_extents).into(), (), mesh_storage)
+            })
+            .clone()
+    }
+
+    pub fn texture(
+        &mut self,
+        surface: &Surface,
+        loader: &Loader,
+        tex_storage: &AssetStorage<Texture>,
+    ) -> TextureHandle {
+        if let Some(texture) = self.textures.get(surface) {
+            return texture.clone();
+        }
+        let texture = match *surface {
+            Surface::Color(color) => {
+                loader.load_from_data(dequantize(color).into(), (), tex_storage)
+            }
+            Surface::Pattern(color, pattern) => {
+                loader.load_from_data(pattern_texture(dequantize(color), pattern), (), tex_storage)
+            }
+            Surface::Texture(ref name) => loader.load(
+                format!("resources/textures/{}.png", name),
+                PngFormat,
+                TextureMetadata::srgb()
+                    .with_sampler(SamplerInfo::new(FilterMethod::Trilinear, WrapMode::Tile)),
+                (),
+                tex_storage,
+            ),
+        };
+        self.textures.insert(surface.clone(), texture.clone());
+        texture
+    }
+
+    pub fn grabbable(
+        &mut self,
+        surface: Surface,
+        kind: MaterialKind,
+        loader: &Loader,
+        tex_storage: &AssetStorage<Texture>,
+        defaults: &Material,
+    ) -> Grabbable {
+        let key = (surface, kind);
+        if let Some(g) = self.grabbables.get(&key) {
+            return g.clone();
+        }
+        let texture = self.texture(&key.0, loader, tex_storage);
+        let roughness = self
+            .roughness
+            .entry(kind)
+            .or_insert_with(|| {
+                let r = kind.roughness();
+                loader.load_from_data([r, r, r, 1.0].into(), (), tex_storage)
+            })
+            .clone();
+        let g = Grabbable {
+            default_material: Material {
+                albedo: texture.clone(),
+                roughness: roughness.clone(),
+                ..defaults.clone()
+            },
+            selected_material: Material {
+                metallic: texture,
+                roughness,
+                ..defaults.clone()
+            },
+        };
+        self.grabbables.insert(key, g.clone());
+        g
+    }
+}
@@ -0,0 +1,38 @@
+/// Where `CameraSystem` puts the camera, `next_camera` goes through them in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraMode {
+    /// From the player's eyes.
+    FirstPerson,
+    /// Behind the player, looking where they look.
+    ThirdPerson,
+    /// Around the selection, or the block pointed at when the mode was picked.
+    Orbit,
+    /// Flying on its own, leaving the player where they stand.
+    FreeFly,
+}
+
+impl Default for CameraMode {
+    fn default() -> Self {
+        CameraMode::FirstPerson
+    }
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            CameraMode::FirstPerson => CameraMode::ThirdPerson,
+            CameraMode::ThirdPerson => CameraMode::Orbit,
+            CameraMode::Orbit => CameraMode::FreeFly,
+            CameraMode::FreeFly => CameraMode::FirstPerson,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CameraMode::FirstPerson => "first person",
+            CameraMode::ThirdPerson => "third person",
+            CameraMode::Orbit => "orbit",
+            CameraMode::FreeFly => "free fly",
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use specs::Entity;
+
+/// Published on an `EventChannel<CollisionEvent>` by `PhysicsSystem` after every step.
+#[derive(Clone, Copy, Debug)]
+pub enum CollisionEvent {
+    CollisionStarted(Entity, Entity),
+    CollisionEnded(Entity, Entity),
+    /// Sent along with `CollisionStarted`. `impulse` is estimated from the change of momentum
+    /// of the bodies during the step the contact started, nphysics doesn't expose the solver's.
+    Impact {
+        a: Entity,
+        b: Entity,
+        impulse: f32,
+    },
+}
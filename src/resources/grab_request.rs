@@ -0,0 +1,12 @@
+use specs::Entity;
+
+/// Published on an `EventChannel<GrabRequest>` to make `MoveSystem` hold `entity`, e.g. a copy
+/// that was just spawned, dropping whatever it held before.
+#[derive(Clone, Debug)]
+pub struct GrabRequest {
+    pub entity: Entity,
+    /// Carried along with `entity`, keeping their current pose relative to it.
+    pub members: Vec<Entity>,
+    /// How far in front of the camera `entity` is held.
+    pub distance: f32,
+}
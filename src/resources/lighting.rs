@@ -0,0 +1,206 @@
+use amethyst::renderer::{DirectionalLight, Light, PointLight, Rgba, SpotLight};
+
+/// What shape of light it is and where it shines from.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum LightKind {
+    /// Lights everything from `direction`, like the sun.
+    Directional {
+        direction: [f32; 3],
+    },
+    Point {
+        position: [f32; 3],
+        radius: f32,
+    },
+    /// A cone of `angle` degrees pointing at `direction`.
+    Spot {
+        position: [f32; 3],
+        direction: [f32; 3],
+        angle: f32,
+        range: f32,
+    },
+}
+
+/// One light of the rig, its color is scaled by its intensity.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LightDesc {
+    pub kind: LightKind,
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl LightDesc {
+    pub fn light(&self) -> Light {
+        let [r, g, b] = self.color;
+        match self.kind {
+            LightKind::Directional { direction } => {
+                let i = self.intensity;
+                Light::Directional(DirectionalLight {
+                    color: Rgba(r * i, g * i, b * i, 1.0),
+                    direction,
+                })
+            }
+            LightKind::Point { radius, .. } => Light::Point(PointLight {
+                color: Rgba(r, g, b, 1.0),
+                intensity: self.intensity,
+                radius,
+                ..PointLight::default()
+            }),
+            LightKind::Spot {
+                direction,
+                angle,
+                range,
+                ..
+            } => Light::Spot(SpotLight {
+                angle: angle.to_radians(),
+                color: Rgba(r, g, b, 1.0),
+                direction,
+                intensity: self.intensity,
+                range,
+                ..SpotLight::default()
+            }),
+        }
+    }
+
+    /// Where its entity goes, directional lights are placed at the origin.
+    pub fn position(&self) -> [f32; 3] {
+        match self.kind {
+            LightKind::Directional { .. } => [0.0, 0.0, 0.0],
+            LightKind::Point { position, .. } | LightKind::Spot { position, .. } => position,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let kind = match self.kind {
+            LightKind::Directional { .. } => "directional",
+            LightKind::Point { .. } => "point",
+            LightKind::Spot { .. } => "spot",
+        };
+        format!("{} light, intensity {:.2}", kind, self.intensity)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ambient {
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl Ambient {
+    pub fn rgba(&self) -> Rgba {
+        let [r, g, b] = self.color;
+        let i = self.intensity;
+        Rgba(r * i, g * i, b * i, 1.0)
+    }
+}
+
+/// The point light lamp blocks shine with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Lamp {
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub radius: f32,
+}
+
+impl Lamp {
+    pub fn light(&self) -> Light {
+        let [r, g, b] = self.color;
+        Light::Point(PointLight {
+            color: Rgba(r, g, b, 1.0),
+            intensity: self.intensity,
+            radius: self.radius,
+            ..PointLight::default()
+        })
+    }
+}
+
+/// How the sky and the sun change over a day, see `TimeOfDay`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Sky {
+    /// Real seconds a whole day lasts.
+    pub day_length: f32,
+    /// The index in `Lighting::lights` of the directional light that follows the sun, if any.
+    pub sun: Option<usize>,
+    /// How much of the ambient light is left at night.
+    pub night_ambient: f32,
+    pub day_color: [f32; 3],
+    pub night_color: [f32; 3],
+    /// Blended near the horizon while the sun rises or sets.
+    pub dusk_color: [f32; 3],
+}
+
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
+
+impl Sky {
+    /// The color of the sky at its zenith and at the horizon with `daylight` between 0 at night
+    /// and 1 at day.
+    pub fn colors(&self, daylight: f32) -> (Rgba, Rgba) {
+        let zenith = mix(self.night_color, self.day_color, daylight);
+        // strongest halfway through sunrise and sunset
+        let dusk = daylight * (1.0 - daylight) * 4.0;
+        let horizon = mix(zenith, self.dusk_color, dusk);
+        let rgba = |[r, g, b]: [f32; 3]| Rgba(r, g, b, 1.0);
+        (rgba(zenith), rgba(horizon))
+    }
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Sky {
+            day_length: 600.0,
+            sun: Some(5),
+            night_ambient: 0.3,
+            day_color: [30.0 / 255.0, 144.0 / 255.0, 1.0],
+            night_color: [0.01, 0.02, 0.06],
+            dusk_color: [0.95, 0.45, 0.2],
+        }
+    }
+}
+
+/// The scene's lights, loaded from `resources/lighting.ron` through `Config`.
+///
+/// `LightingSystem` creates an entity for each of `lights` and keeps them in sync as they are
+/// adjusted from the lighting panel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Lighting {
+    pub ambient: Ambient,
+    pub lights: Vec<LightDesc>,
+    pub lamp: Lamp,
+    #[serde(default)]
+    pub sky: Sky,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        let directional = |direction, intensity| LightDesc {
+            kind: LightKind::Directional { direction },
+            color: [1.0, 1.0, 1.0],
+            intensity,
+        };
+        Lighting {
+            ambient: Ambient {
+                color: [1.0, 1.0, 1.0],
+                intensity: 0.3,
+            },
+            lights: vec![
+                directional([-1.0, 0.0, 0.0], 0.08),
+                directional([1.0, 0.0, 0.0], 0.09),
+                directional([0.0, 0.0, -1.0], 0.10),
+                directional([0.0, 0.0, 1.0], 0.11),
+                directional([0.0, -1.0, 0.0], 0.12),
+                directional([0.3, -1.0, 0.3], 0.6),
+            ],
+            lamp: Lamp {
+                color: [1.0, 0.85, 0.6],
+                intensity: 4.0,
+                radius: 6.0,
+            },
+            sky: Sky::default(),
+        }
+    }
+}
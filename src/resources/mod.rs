@@ -1,3 +1,37 @@
+mod accessibility;
+mod block_assets;
+mod camera_mode;
+mod collision_event;
+mod grab_request;
+mod lighting;
 mod my_world;
+mod paint;
+mod palettes;
+mod physics_clock;
+mod physics_history;
+mod physics_stats;
+mod picker;
+mod resumed;
+mod saved_game;
+mod settings;
+mod sounds;
+mod time_of_day;
 
-pub use self::my_world::MyWorld;
+pub use self::accessibility::Accessibility;
+pub use self::block_assets::{BlockAssets, Surface, TEXTURES};
+pub use self::camera_mode::CameraMode;
+pub use self::collision_event::CollisionEvent;
+pub use self::grab_request::GrabRequest;
+pub use self::lighting::{LightDesc, LightKind, Lighting};
+pub use self::my_world::{MyWorld, BLOCK_GROUP, PLAYER_GROUP, STATIC_GROUP};
+pub use self::paint::Paint;
+pub use self::palettes::Palettes;
+pub use self::physics_clock::PhysicsClock;
+pub use self::physics_history::PhysicsHistory;
+pub use self::physics_stats::PhysicsStats;
+pub use self::picker::{PickHit, Picker};
+pub use self::resumed::Resumed;
+pub use self::saved_game::SavedGame;
+pub use self::settings::{Settings, FOV_RANGE, SENSITIVITY_RANGE, SPEED_RANGE};
+pub use self::sounds::{Sounds, UiSound};
+pub use self::time_of_day::TimeOfDay;
@@ -1,16 +1,114 @@
-use nphysics3d::world::World as PhysicsWorld;
-use std::ops::{Deref, DerefMut};
+use ncollide3d::world::{CollisionGroups, CollisionObjectHandle};
+use nphysics3d::{
+    object::{BodyHandle, BodyStatus},
+    world::World as PhysicsWorld,
+};
+use specs::Entity;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::{Deref, DerefMut},
+};
+
+/// Collision group memberships, used to filter ray casts.
+pub const BLOCK_GROUP: usize = 0;
+pub const PLAYER_GROUP: usize = 1;
+pub const STATIC_GROUP: usize = 2;
 
 type MyCollisionWorld = PhysicsWorld<f32>;
 pub struct MyWorld {
     pub inner: MyCollisionWorld,
+    /// The entity of each collider given to `attach`.
+    collider_entities: HashMap<CollisionObjectHandle, Entity>,
+    /// The collider of each body given to `attach`, so `remove_bodies` can forget it.
+    body_colliders: HashMap<BodyHandle, CollisionObjectHandle>,
 }
 
 impl Default for MyWorld {
     fn default() -> Self {
         MyWorld {
             inner: MyCollisionWorld::new(),
+            collider_entities: HashMap::new(),
+            body_colliders: HashMap::new(),
+        }
+    }
+}
+
+impl MyWorld {
+    /// Remembers that `collider` belongs to `entity`, replacing whatever the collider's body
+    /// was attached to.
+    pub fn attach(&mut self, collider: CollisionObjectHandle, entity: Entity) {
+        self.collider_entities.insert(collider, entity);
+        if let Some(bh) = self.collider_body_handle(collider) {
+            if !bh.is_ground() {
+                self.body_colliders.insert(bh, collider);
+            }
+        }
+    }
+
+    /// The entity `collider` was attached to, if any.
+    pub fn collider_entity(&self, collider: CollisionObjectHandle) -> Option<Entity> {
+        self.collider_entities.get(&collider).cloned()
+    }
+
+    /// The entity the collider of `body` was attached to, if any.
+    pub fn body_entity(&self, body: BodyHandle) -> Option<Entity> {
+        self.body_colliders
+            .get(&body)
+            .and_then(|collider| self.collider_entity(*collider))
+    }
+
+    /// Removes the bodies along with their colliders, forgetting the entities they were attached
+    /// to.
+    pub fn remove_bodies(&mut self, handles: &[BodyHandle]) {
+        for bh in handles {
+            if let Some(collider) = self.body_colliders.remove(bh) {
+                self.collider_entities.remove(&collider);
+            }
+        }
+        self.inner.remove_bodies(handles);
+    }
+
+    pub fn set_membership(&mut self, collider: CollisionObjectHandle, group: usize) {
+        self.inner
+            .collision_world_mut()
+            .set_collision_groups(collider, CollisionGroups::new().with_membership(&[group]));
+    }
+
+    fn is_dynamic(&self, collider: CollisionObjectHandle) -> bool {
+        self.collider_body_handle(collider)
+            .and_then(|bh| self.rigid_body(bh))
+            .map(|rb| rb.status() == BodyStatus::Dynamic)
+            .unwrap_or(false)
+    }
+
+    /// Colliders of the dynamic bodies forming a structure with `start`, that is, connected to
+    /// it through contacts. Static colliders like the floor don't join structures together.
+    pub fn connected_colliders(
+        &self,
+        start: CollisionObjectHandle,
+    ) -> HashSet<CollisionObjectHandle> {
+        let mut neighbours: HashMap<CollisionObjectHandle, Vec<CollisionObjectHandle>> =
+            HashMap::new();
+        for (co1, co2, contacts) in self.collision_world().contact_pairs() {
+            let (h1, h2) = (co1.handle(), co2.handle());
+            if contacts.num_contacts() == 0 || !self.is_dynamic(h1) || !self.is_dynamic(h2) {
+                continue;
+            }
+            neighbours.entry(h1).or_insert_with(Vec::new).push(h2);
+            neighbours.entry(h2).or_insert_with(Vec::new).push(h1);
+        }
+
+        let mut structure = HashSet::new();
+        let mut pending = vec![start];
+        while let Some(handle) = pending.pop() {
+            if !structure.insert(handle) {
+                continue;
+            }
+            if let Some(next) = neighbours.get(&handle) {
+                pending.extend(next.iter().filter(|h| !structure.contains(h)));
+            }
         }
+        structure
     }
 }
 
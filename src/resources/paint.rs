@@ -0,0 +1,110 @@
+use super::Palettes;
+
+pub const MAX_SAVED_SWATCHES: usize = 8;
+
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let c = value * saturation;
+    let h = hue / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = value - c;
+    [r + m, g + m, b + m]
+}
+
+fn rgb_to_hsv(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let hue = if delta <= 0.0 {
+        0.0
+    } else if r >= g && r >= b {
+        60.0 * ((g - b) / delta)
+    } else if g >= b {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max <= 0.0 { 0.0 } else { delta / max };
+    ((hue + 360.0) % 360.0, saturation, max)
+}
+
+/// The paint tool's color, kept as HSV so the picker can adjust each channel, the palette the
+/// swatch keys pick from and the swatches saved this session.
+pub struct Paint {
+    /// In degrees.
+    pub hue: f32,
+    pub saturation: f32,
+    pub value: f32,
+    /// Index into `Palettes`, one past the last one shows the saved swatches.
+    pub palette: usize,
+    pub saved: Vec<[f32; 4]>,
+}
+
+impl Default for Paint {
+    fn default() -> Self {
+        Paint {
+            hue: 120.0,
+            saturation: 1.0,
+            value: 1.0,
+            palette: 0,
+            saved: vec![],
+        }
+    }
+}
+
+impl Paint {
+    pub fn color(&self) -> [f32; 4] {
+        let [r, g, b] = hsv_to_rgb(self.hue, self.saturation, self.value);
+        [r, g, b, 1.0]
+    }
+
+    pub fn set_color(&mut self, color: [f32; 4]) {
+        let (hue, saturation, value) = rgb_to_hsv(color[0], color[1], color[2]);
+        self.hue = hue;
+        self.saturation = saturation;
+        self.value = value;
+    }
+
+    pub fn adjust(&mut self, hue: f32, saturation: f32, value: f32) {
+        self.hue = (self.hue + hue + 360.0) % 360.0;
+        self.saturation = (self.saturation + saturation).max(0.0).min(1.0);
+        self.value = (self.value + value).max(0.0).min(1.0);
+    }
+
+    pub fn next_palette(&mut self, palettes: &Palettes) {
+        self.palette = (self.palette + 1) % (palettes.palettes.len() + 1);
+    }
+
+    /// The current palette's name and colors.
+    pub fn swatches<'a>(&'a self, palettes: &'a Palettes) -> (&'a str, &'a [[f32; 4]]) {
+        match palettes.palettes.get(self.palette) {
+            Some(palette) => (&palette.name, &palette.colors),
+            None => ("Saved", &self.saved),
+        }
+    }
+
+    pub fn save_swatch(&mut self) {
+        if self.saved.len() == MAX_SAVED_SWATCHES {
+            self.saved.remove(0);
+        }
+        let color = self.color();
+        self.saved.push(color);
+    }
+
+    pub fn describe(&self, palettes: &Palettes) -> String {
+        format!(
+            "paint: hue {:.0}, saturation {:.0}%, value {:.0}%, palette {}",
+            self.hue,
+            self.saturation * 100.0,
+            self.value * 100.0,
+            self.swatches(palettes).0
+        )
+    }
+}
@@ -0,0 +1,31 @@
+use crate::blocks::COLORS;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Palette {
+    pub name: String,
+    pub colors: Vec<[f32; 4]>,
+}
+
+/// The paint tool's palettes, loaded from `resources/palettes.ron` through `Config`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Palettes {
+    pub palettes: Vec<Palette>,
+}
+
+impl Default for Palettes {
+    fn default() -> Self {
+        Palettes {
+            palettes: vec![Palette {
+                name: "Classic".to_owned(),
+                colors: COLORS.to_vec(),
+            }],
+        }
+    }
+}
+
+impl Palettes {
+    /// The index of the palette called `name`, if there is one.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.palettes.iter().position(|p| p.name == name)
+    }
+}
@@ -0,0 +1,46 @@
+pub const MIN_TIME_SCALE: f32 = 0.1;
+pub const MAX_TIME_SCALE: f32 = 4.0;
+
+pub struct PhysicsClock {
+    pub paused: bool,
+    pub time_scale: f32,
+    pub pending_steps: u32,
+}
+
+impl Default for PhysicsClock {
+    fn default() -> Self {
+        PhysicsClock {
+            paused: false,
+            time_scale: 1.0,
+            pending_steps: 0,
+        }
+    }
+}
+
+impl PhysicsClock {
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.pending_steps = 0;
+    }
+
+    pub fn scale_by(&mut self, factor: f32) {
+        self.time_scale = (self.time_scale * factor)
+            .max(MIN_TIME_SCALE)
+            .min(MAX_TIME_SCALE);
+    }
+
+    /// Single steps only make sense while the simulation is frozen.
+    pub fn request_step(&mut self) {
+        if self.paused {
+            self.pending_steps += 1;
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        if self.paused {
+            "physics: paused".to_owned()
+        } else {
+            format!("physics: {:.2}x", self.time_scale)
+        }
+    }
+}
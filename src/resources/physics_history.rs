@@ -0,0 +1,111 @@
+use std::{collections::VecDeque, mem};
+
+use crate::resources::MyWorld;
+
+use na::Isometry3;
+use nphysics3d::{
+    math::Velocity,
+    object::{BodyHandle, BodyStatus},
+};
+use specs::Entity;
+
+pub const DEFAULT_MEMORY_BUDGET: usize = 16 * 1024 * 1024;
+
+struct BodyState {
+    handle: BodyHandle,
+    /// The body's entity, nphysics gives the handles of removed bodies to new ones.
+    entity: Entity,
+    position: Isometry3<f32>,
+    velocity: Velocity<f32>,
+}
+
+type Snapshot = Vec<BodyState>;
+
+fn snapshot_size(snapshot: &Snapshot) -> usize {
+    mem::size_of::<Snapshot>() + snapshot.capacity() * mem::size_of::<BodyState>()
+}
+
+/// Rolling buffer of the dynamic bodies' poses and velocities, taken right before each
+/// physics step.
+/// The oldest snapshots are dropped once `memory_budget` bytes are in use.
+pub struct PhysicsHistory {
+    snapshots: VecDeque<Snapshot>,
+    used_bytes: usize,
+    pub memory_budget: usize,
+    pub rewinding: bool,
+}
+
+impl Default for PhysicsHistory {
+    fn default() -> Self {
+        PhysicsHistory::with_memory_budget(DEFAULT_MEMORY_BUDGET)
+    }
+}
+
+impl PhysicsHistory {
+    pub fn with_memory_budget(memory_budget: usize) -> Self {
+        PhysicsHistory {
+            snapshots: VecDeque::new(),
+            used_bytes: 0,
+            memory_budget,
+            rewinding: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn record(&mut self, physics_world: &MyWorld, handles: &[BodyHandle]) {
+        let snapshot: Snapshot = handles
+            .iter()
+            .filter_map(|bh| physics_world.rigid_body(*bh).map(|rb| (*bh, rb)))
+            .filter(|(_, rb)| rb.status() == BodyStatus::Dynamic)
+            .filter_map(|(handle, rb)| {
+                physics_world
+                    .body_entity(handle)
+                    .map(|entity| (handle, entity, rb))
+            })
+            .map(|(handle, entity, rb)| BodyState {
+                handle,
+                entity,
+                position: *rb.position(),
+                velocity: *rb.velocity(),
+            })
+            .collect();
+        self.used_bytes += snapshot_size(&snapshot);
+        self.snapshots.push_back(snapshot);
+        while self.used_bytes > self.memory_budget {
+            match self.snapshots.pop_front() {
+                Some(oldest) => self.used_bytes -= snapshot_size(&oldest),
+                None => break,
+            }
+        }
+    }
+
+    /// Restores the most recent snapshot and forgets it, so simulating afterwards continues
+    /// from the restored step. Returns `false` when there is nothing left to rewind.
+    pub fn rewind(&mut self, physics_world: &mut MyWorld) -> bool {
+        let snapshot = match self.snapshots.pop_back() {
+            Some(x) => x,
+            None => return false,
+        };
+        self.used_bytes -= snapshot_size(&snapshot);
+        for state in snapshot.iter() {
+            // bodies removed since the snapshot was taken are skipped, even when their handle
+            // now belongs to another body
+            if physics_world.body_entity(state.handle) != Some(state.entity) {
+                continue;
+            }
+            if let Some(rb) = physics_world.rigid_body_mut(state.handle) {
+                rb.set_position(state.position);
+                rb.set_velocity(state.velocity);
+                rb.activate();
+            }
+        }
+        true
+    }
+}
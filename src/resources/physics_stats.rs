@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+#[derive(Default)]
+pub struct PhysicsStats {
+    pub steps: u64,
+    /// Steps that were due but skipped because a frame needed more than `MAX_STEPS_PER_RUN`.
+    pub dropped_steps: u64,
+    /// Bodies whose transform was written in the last frame, only awake ones are synced.
+    pub synced_bodies: usize,
+    pub sync_time: Duration,
+}
+
+impl PhysicsStats {
+    pub fn describe(&self) -> String {
+        format!(
+            "steps: {} (dropped {}), synced {} bodies in {:.3}ms",
+            self.steps,
+            self.dropped_steps,
+            self.synced_bodies,
+            f64::from(self.sync_time.subsec_nanos()) / 1_000_000.0,
+        )
+    }
+}
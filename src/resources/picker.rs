@@ -0,0 +1,29 @@
+use na::{Point3, Vector3};
+use specs::Entity;
+
+/// What the crosshair points at, updated once per frame by `PickSystem` so every tool shares
+/// the same ray cast.
+#[derive(Clone, Copy)]
+pub struct PickHit {
+    /// `None` when the collider that was hit isn't attached to an entity.
+    pub entity: Option<Entity>,
+    pub toi: f32,
+    /// How far the hit is from the player's eyes in third person, where the camera is behind
+    /// them, and from the camera otherwise.
+    pub reach: f32,
+    pub point: Point3<f32>,
+    pub normal: Vector3<f32>,
+}
+
+#[derive(Default)]
+pub struct Picker {
+    pub hit: Option<PickHit>,
+}
+
+impl Picker {
+    pub fn entity_within(&self, max_reach: f32) -> Option<(Entity, PickHit)> {
+        self.hit
+            .filter(|hit| hit.reach < max_reach)
+            .and_then(|hit| hit.entity.map(|e| (e, hit)))
+    }
+}
@@ -0,0 +1,6 @@
+/// Set by `CustomGameData::update` for the first frame the gameplay systems run after being
+/// frozen behind a menu.
+///
+/// Systems reading input skip the events queued on that frame, they were meant for the menu.
+#[derive(Default)]
+pub struct Resumed(pub bool);
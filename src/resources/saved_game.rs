@@ -0,0 +1,88 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use crate::components::{Block, Locked, MaterialKind};
+
+use amethyst::{
+    config::Config,
+    core::{Named, Transform},
+    ecs::{Join, World},
+};
+
+use dirs;
+
+/// A block as it is written to the save file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedBlock {
+    pub name: String,
+    pub half_extents: [f32; 3],
+    pub translation: [f32; 3],
+    /// The rotation's axis scaled by its angle in radians.
+    pub rotation: [f32; 3],
+    pub kind: MaterialKind,
+    pub color: [f32; 4],
+    pub texture: Option<String>,
+    pub locked: bool,
+}
+
+/// The blocks of a sandbox, kept in `save.ron` in the user's data directory.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub blocks: Vec<SavedBlock>,
+}
+
+impl SavedGame {
+    /// Where the game is saved, `None` when the platform has no data directory.
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("building-stuff").join("save.ron"))
+    }
+
+    /// Takes every block of `world` where it currently is, held ones included.
+    pub fn capture(world: &World) -> Self {
+        let names = world.read_storage::<Named>();
+        let blocks = world.read_storage::<Block>();
+        let kinds = world.read_storage::<MaterialKind>();
+        let transforms = world.read_storage::<Transform>();
+        let locked = world.read_storage::<Locked>();
+        let blocks = (
+            &*world.entities(),
+            &blocks,
+            &kinds,
+            &transforms,
+            names.maybe(),
+        )
+            .join()
+            .map(|(entity, block, kind, t, name)| {
+                let h = block.half_extents;
+                let position = t.translation();
+                let rotation = t.rotation().scaled_axis();
+                SavedBlock {
+                    name: name.map_or_else(String::new, |n| n.name.to_string()),
+                    half_extents: [h.x, h.y, h.z],
+                    translation: [position.x, position.y, position.z],
+                    rotation: [rotation.x, rotation.y, rotation.z],
+                    kind: *kind,
+                    color: block.color,
+                    texture: block.texture.clone(),
+                    locked: locked.contains(entity),
+                }
+            })
+            .collect();
+        SavedGame { blocks }
+    }
+
+    /// Reads the save file.
+    pub fn load_saved() -> Result<Self, Box<dyn Error>> {
+        let path = Self::path().ok_or("no data directory")?;
+        Ok(Self::load_no_fallback(&path)?)
+    }
+
+    /// Writes the save file, creating its directory if needed.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Self::path().ok_or("no data directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        self.write(&path)?;
+        Ok(())
+    }
+}
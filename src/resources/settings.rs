@@ -0,0 +1,229 @@
+use std::{collections::BTreeMap, error::Error, fs, path::PathBuf};
+
+use amethyst::{
+    config::Config,
+    input::{Axis, Bindings, Button},
+};
+
+use dirs;
+
+pub const SENSITIVITY_RANGE: (f32, f32) = (0.01, 5.0);
+pub const SPEED_RANGE: (f32, f32) = (0.1, 50.0);
+pub const FOV_RANGE: (f32, f32) = (30.0, 120.0);
+
+/// The player's preferences, kept in `settings.ron` in the user's config directory.
+///
+/// Fields missing from the file take their default value.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Degrees the camera turns per pixel the mouse moves sideways.
+    pub sensitivity_x: f32,
+    /// Degrees the camera turns per pixel the mouse moves up or down.
+    pub sensitivity_y: f32,
+    /// Moving the mouse up looks down.
+    pub invert_y: bool,
+    /// Units per second when walking.
+    pub speed: f32,
+    /// Units per second while `run` is held.
+    pub speed_running: f32,
+    /// The vertical field of view, in degrees.
+    pub fov: f32,
+    /// Dropped blocks are moved onto the building grid.
+    pub snap: bool,
+    /// The palette of `resources/palettes.ron` the first blocks are colored from and the paint
+    /// tool starts on.
+    pub palette: String,
+    /// Buttons that replace the ones `resources/input.ron` binds to these actions.
+    pub bindings: BTreeMap<String, Vec<Button>>,
+    /// Replacements for these axes of `resources/input.ron`, like `move_x` and `move_z`.
+    pub axes: BTreeMap<String, Axis>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            sensitivity_x: 0.2,
+            sensitivity_y: 0.2,
+            invert_y: false,
+            speed: 1.0,
+            speed_running: 5.0,
+            fov: 60.0,
+            snap: false,
+            palette: "Okabe-Ito".to_owned(),
+            bindings: BTreeMap::new(),
+            axes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Puts `value` back to `default` unless it is within `range`, describing why.
+fn check(name: &str, value: &mut f32, (min, max): (f32, f32), default: f32) -> Option<String> {
+    if *value >= min && *value <= max {
+        return None;
+    }
+    let problem = format!(
+        "{} is {} but must be between {} and {}, using {}",
+        name, value, min, max, default
+    );
+    *value = default;
+    Some(problem)
+}
+
+impl Settings {
+    /// Where the settings are kept, `None` when the platform has no config directory.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("building-stuff").join("settings.ron"))
+    }
+
+    /// Reads the settings file, writing one with the defaults the first time.
+    ///
+    /// Settings that can't be read or are out of range are logged and replaced by defaults.
+    pub fn load_or_create() -> Self {
+        let path = match Self::path() {
+            Some(x) => x,
+            None => {
+                warn!("no config directory, using the default settings");
+                return Settings::default();
+            }
+        };
+        if !path.exists() {
+            let settings = Settings::default();
+            match settings.save() {
+                Ok(()) => info!("wrote the default settings to {}", path.display()),
+                Err(e) => warn!("couldn't write {}: {}", path.display(), e),
+            }
+            return settings;
+        }
+        let mut settings = match Settings::load_no_fallback(&path) {
+            Ok(x) => x,
+            Err(e) => {
+                error!(
+                    "couldn't read {}, using the default settings: {}",
+                    path.display(),
+                    e
+                );
+                return Settings::default();
+            }
+        };
+        for problem in settings.validate() {
+            warn!("{}: {}", path.display(), problem);
+        }
+        settings
+    }
+
+    /// Writes the settings to `path`, creating its directory if needed.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Self::path().ok_or("no config directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        self.write(&path)?;
+        Ok(())
+    }
+
+    /// Replaces the values that are out of range with their default, returning what was wrong.
+    pub fn validate(&mut self) -> Vec<String> {
+        let defaults = Settings::default();
+        vec![
+            check(
+                "sensitivity_x",
+                &mut self.sensitivity_x,
+                SENSITIVITY_RANGE,
+                defaults.sensitivity_x,
+            ),
+            check(
+                "sensitivity_y",
+                &mut self.sensitivity_y,
+                SENSITIVITY_RANGE,
+                defaults.sensitivity_y,
+            ),
+            check("speed", &mut self.speed, SPEED_RANGE, defaults.speed),
+            check(
+                "speed_running",
+                &mut self.speed_running,
+                SPEED_RANGE,
+                defaults.speed_running,
+            ),
+            check("fov", &mut self.fov, FOV_RANGE, defaults.fov),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// The vertical field of view in radians.
+    pub fn fov_radians(&self) -> f32 {
+        self.fov.to_radians()
+    }
+
+    /// Rebinds the actions of `bindings` and the axes of `axes` in `input`, logging those that
+    /// can't be bound.
+    pub fn apply_bindings(&self, input: &mut Bindings<String, String>) {
+        // every replaced axis is removed first, so the new ones can take each other's buttons
+        for axis in self.axes.keys() {
+            input.remove_axis(axis);
+        }
+        for (name, axis) in &self.axes {
+            if let Err(e) = input.insert_axis(name.clone(), axis.clone()) {
+                warn!("couldn't bind {:?} to {}: {:?}", axis, name, e);
+            }
+        }
+        for (action, buttons) in &self.bindings {
+            let old: Vec<Button> = input.action_bindings(action).cloned().collect();
+            for button in old {
+                input.remove_action_binding(action, button);
+            }
+            for button in buttons {
+                if let Err(e) = input.insert_action_binding(action.clone(), *button) {
+                    warn!("couldn't bind {:?} to {}: {:?}", button, action, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_keeps_values_in_range() {
+        let mut settings = Settings::default();
+        settings.fov = FOV_RANGE.1;
+        assert!(settings.validate().is_empty());
+        assert_eq!(settings.fov, FOV_RANGE.1);
+    }
+
+    #[test]
+    fn validate_replaces_out_of_range_values() {
+        let mut settings = Settings::default();
+        settings.speed = SPEED_RANGE.1 + 1.0;
+        settings.sensitivity_x = 0.0;
+        let problems = settings.validate();
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().any(|p| p.starts_with("speed ")));
+        assert!(problems.iter().any(|p| p.starts_with("sensitivity_x ")));
+        assert_eq!(settings.speed, Settings::default().speed);
+        assert_eq!(settings.sensitivity_x, Settings::default().sensitivity_x);
+    }
+
+    #[test]
+    fn validate_replaces_nan() {
+        let mut settings = Settings::default();
+        settings.fov = ::std::f32::NAN;
+        assert_eq!(settings.validate().len(), 1);
+        assert_eq!(settings.fov, Settings::default().fov);
+    }
+
+    #[test]
+    fn missing_fields_take_their_default() {
+        let mut settings = Settings::load_bytes(b"(fov: 90.0, invert_y: true)").unwrap();
+        assert!(settings.validate().is_empty());
+        assert_eq!(settings.fov, 90.0);
+        assert!(settings.invert_y);
+        assert_eq!(settings.speed, Settings::default().speed);
+        assert!(settings.bindings.is_empty());
+        assert!(settings.axes.is_empty());
+    }
+}
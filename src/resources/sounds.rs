@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use crate::components::MaterialKind;
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    audio::{Source, SourceHandle, WavFormat},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UiSound {
+    Grab,
+    Drop,
+    Spawn,
+}
+
+impl UiSound {
+    pub const ALL: [UiSound; 3] = [UiSound::Grab, UiSound::Drop, UiSound::Spawn];
+
+    fn name(self) -> &'static str {
+        match self {
+            UiSound::Grab => "grab",
+            UiSound::Drop => "drop",
+            UiSound::Spawn => "spawn",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Sounds {
+    pub impacts: HashMap<MaterialKind, SourceHandle>,
+    pub ui: HashMap<UiSound, SourceHandle>,
+}
+
+impl Sounds {
+    pub fn load(loader: &Loader, storage: &AssetStorage<Source>) -> Self {
+        let load = |name: String| {
+            loader.load(
+                format!("resources/audio/{}.wav", name),
+                WavFormat,
+                (),
+                (),
+                storage,
+            )
+        };
+        Sounds {
+            impacts: MaterialKind::ALL
+                .iter()
+                .map(|kind| (*kind, load(format!("{}_impact", kind.name()))))
+                .collect(),
+            ui: UiSound::ALL
+                .iter()
+                .map(|sound| (*sound, load(sound.name().to_owned())))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::f32::consts::PI;
+
+use amethyst::core::nalgebra::Vector3;
+
+const HOURS: f32 = 24.0;
+/// Daylight fades in while the sun rises from this far below the horizon, as the sine of its
+/// elevation, until it is as far above.
+const TWILIGHT: f32 = 0.15;
+
+/// The time of day the sun, the ambient light and the sky follow, see `Sky`.
+pub struct TimeOfDay {
+    /// From 0 to 24, the sun rises at 6 and sets at 18.
+    pub hour: f32,
+    pub paused: bool,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        TimeOfDay {
+            hour: 10.0,
+            paused: false,
+        }
+    }
+}
+
+impl TimeOfDay {
+    /// Moves the clock by `hours`, forward or backward, wrapping around midnight.
+    pub fn advance(&mut self, hours: f32) {
+        let hour = (self.hour + hours) % HOURS;
+        self.hour = if hour < 0.0 { hour + HOURS } else { hour };
+    }
+
+    /// The angle of the sun along its path, 0 when it rises and PI when it sets.
+    fn sun_angle(&self) -> f32 {
+        (self.hour - 6.0) / 12.0 * PI
+    }
+
+    /// The direction the sun shines in, it rises in the east (+x) and sets in the west.
+    pub fn sun_direction(&self) -> [f32; 3] {
+        let angle = self.sun_angle();
+        let towards_sun = Vector3::new(angle.cos(), angle.sin(), 0.3).normalize();
+        [-towards_sun.x, -towards_sun.y, -towards_sun.z]
+    }
+
+    /// From 0 at night to 1 at day.
+    pub fn daylight(&self) -> f32 {
+        let elevation = self.sun_angle().sin();
+        ((elevation + TWILIGHT) / (2.0 * TWILIGHT))
+            .max(0.0)
+            .min(1.0)
+    }
+
+    pub fn describe(&self) -> String {
+        let minutes = (self.hour * 60.0) as u32;
+        format!(
+            "Time {:02}:{:02}{}",
+            minutes / 60,
+            minutes % 60,
+            if self.paused { " (paused)" } else { "" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: f32) -> TimeOfDay {
+        TimeOfDay {
+            hour,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn advance_wraps_around_midnight() {
+        let mut time = at(23.0);
+        time.advance(2.0);
+        assert!((time.hour - 1.0).abs() < 1.0e-4);
+        time.advance(-3.0);
+        assert!((time.hour - 22.0).abs() < 1.0e-4);
+        time.advance(-48.0);
+        assert!((time.hour - 22.0).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn daylight_is_full_at_noon_and_none_at_midnight() {
+        assert!((at(12.0).daylight() - 1.0).abs() < 1.0e-4);
+        assert!(at(0.0).daylight().abs() < 1.0e-4);
+        assert!(at(24.0 - 1.0e-3).daylight().abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn daylight_is_half_at_sunrise_and_sunset() {
+        assert!((at(6.0).daylight() - 0.5).abs() < 1.0e-4);
+        assert!((at(18.0).daylight() - 0.5).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn sun_shines_down_at_noon() {
+        let [_, y, _] = at(12.0).sun_direction();
+        assert!(y < -0.9);
+    }
+}
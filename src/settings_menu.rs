@@ -0,0 +1,127 @@
+use crate::{
+    game_data::{CustomGameData, GameTrans},
+    menu,
+    resources::{Settings, FOV_RANGE, SENSITIVITY_RANGE, SPEED_RANGE},
+};
+
+use amethyst::{controls::HideCursor, ecs::Entity, input::is_close_requested, prelude::*};
+
+use winit::VirtualKeyCode;
+
+const SENSITIVITY_STEP: f32 = 0.05;
+const FOV_STEP: f32 = 5.0;
+const SPEED_STEP: f32 = 0.5;
+
+fn step(value: &mut f32, delta: f32, (min, max): (f32, f32)) {
+    *value = (*value + delta).max(min).min(max);
+}
+
+fn on_off(value: bool) -> String {
+    if value { "On" } else { "Off" }.to_owned()
+}
+
+/// The options screen, pushed over `GameState` or one of the menus and built from
+/// `resources/settings_menu.ron`.
+///
+/// Changes apply right away and are saved once the menu is left with `Back` or `Escape`. The
+/// cursor is released while the menu is open so its buttons can be clicked.
+#[derive(Default)]
+pub struct SettingsMenuState {
+    menu: Option<Entity>,
+    /// Whether the cursor was hidden before the menu opened.
+    was_hidden: bool,
+}
+
+impl SettingsMenuState {
+    fn change(settings: &mut Settings, button: &str) {
+        let sign = if button.ends_with("_up") { 1.0 } else { -1.0 };
+        match button {
+            "sensitivity_down" | "sensitivity_up" => {
+                let delta = sign * SENSITIVITY_STEP;
+                step(&mut settings.sensitivity_x, delta, SENSITIVITY_RANGE);
+                step(&mut settings.sensitivity_y, delta, SENSITIVITY_RANGE);
+            }
+            "fov_down" | "fov_up" => step(&mut settings.fov, sign * FOV_STEP, FOV_RANGE),
+            "speed_down" | "speed_up" => step(&mut settings.speed, sign * SPEED_STEP, SPEED_RANGE),
+            "speed_running_down" | "speed_running_up" => {
+                step(&mut settings.speed_running, sign * SPEED_STEP, SPEED_RANGE)
+            }
+            "invert_y_toggle" => settings.invert_y = !settings.invert_y,
+            "snap_toggle" => settings.snap = !settings.snap,
+            _ => (),
+        }
+    }
+
+    /// Writes the current settings next to their buttons, once the menu has loaded.
+    fn show(world: &mut World) {
+        let values = {
+            let settings = world.read_resource::<Settings>();
+            [
+                (
+                    "sensitivity_value",
+                    format!("{:.2}", settings.sensitivity_x),
+                ),
+                ("fov_value", format!("{:.0}°", settings.fov)),
+                ("speed_value", format!("{:.1}", settings.speed)),
+                (
+                    "speed_running_value",
+                    format!("{:.1}", settings.speed_running),
+                ),
+                ("invert_y_value", on_off(settings.invert_y)),
+                ("snap_value", on_off(settings.snap)),
+            ]
+        };
+        menu::set_texts(world, &values);
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for SettingsMenuState {
+    fn on_start(&mut self, data: StateData<CustomGameData>) {
+        {
+            let mut hide = data.world.write_resource::<HideCursor>();
+            self.was_hidden = hide.hide;
+            hide.hide = false;
+        }
+        self.menu = Some(menu::create(data.world, "settings_menu.ron"));
+    }
+
+    fn on_stop(&mut self, data: StateData<CustomGameData>) {
+        if let Some(menu) = self.menu.take() {
+            menu::delete(data.world, menu);
+        }
+        if let Err(e) = data.world.read_resource::<Settings>().save() {
+            warn!("couldn't save the settings: {}", e);
+        }
+        data.world.write_resource::<HideCursor>().hide = self.was_hidden;
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<CustomGameData>,
+        event: StateEvent,
+    ) -> GameTrans<'a, 'b> {
+        let button = match event {
+            StateEvent::Window(ref event) if is_close_requested(event) => return Trans::Quit,
+            StateEvent::Window(ref event)
+                if menu::is_key_pressed(data.world, event, VirtualKeyCode::Escape) =>
+            {
+                Some("back".to_owned())
+            }
+            ref event => menu::clicked(data.world, event),
+        };
+        match button.as_ref().map(String::as_str) {
+            Some("back") => Trans::Pop,
+            Some(button) => {
+                Self::change(&mut data.world.write_resource(), button);
+                Trans::None
+            }
+            None => Trans::None,
+        }
+    }
+
+    fn update(&mut self, data: StateData<CustomGameData>) -> GameTrans<'a, 'b> {
+        data.data.update(data.world, false);
+        Self::show(data.world);
+        Trans::None
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use crate::{
+    blocks::{spawn_block, NewBlock},
+    components::{Block, Grabbable, MaterialKind, PhysicsBody},
+    resources::{BlockAssets, CollisionEvent, MyWorld},
+};
+
+use amethyst::{
+    core::nalgebra::Vector3,
+    ecs::{Entities, Read, ReadExpect, ReadStorage, System, Write},
+    shrev::{EventChannel, ReaderId},
+};
+
+use na::Point3;
+use nphysics3d::math::Velocity;
+use specs::{prelude::Resources, world::LazyUpdate, Entity};
+
+/// Fragments smaller than this don't break any further.
+const MIN_FRAGMENT_HALF_EXTENT: f32 = 0.1;
+
+/// Shatters fragile blocks into eight smaller ones when an impact is strong enough.
+#[derive(Default)]
+pub struct BreakageSystem {
+    event_reader: Option<ReaderId<CollisionEvent>>,
+}
+
+type BreakageSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, EventChannel<CollisionEvent>>,
+    Write<'s, MyWorld>,
+    ReadStorage<'s, PhysicsBody>,
+    ReadStorage<'s, Block>,
+    ReadStorage<'s, MaterialKind>,
+    ReadStorage<'s, Grabbable>,
+    ReadExpect<'s, BlockAssets>,
+    Read<'s, LazyUpdate>,
+);
+
+impl<'s> System<'s> for BreakageSystem {
+    type SystemData = BreakageSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            events,
+            mut physics_world,
+            physics_bodies,
+            blocks,
+            kinds,
+            grabbables,
+            block_assets,
+            lazy,
+        ): Self::SystemData,
+    ) {
+        let mut broken: HashSet<Entity> = HashSet::new();
+        for event in events.read(
+            self.event_reader
+                .as_mut()
+                .expect("`BreakageSystem::setup` was not called before `BreakageSystem::run`"),
+        ) {
+            if let CollisionEvent::Impact { a, b, impulse } = *event {
+                for e in [a, b].iter() {
+                    let breaks = kinds
+                        .get(*e)
+                        .and_then(|kind| kind.break_impulse())
+                        .map(|threshold| impulse > threshold)
+                        .unwrap_or(false);
+                    let big_enough = blocks
+                        .get(*e)
+                        .map(|block| {
+                            let h = block.half_extents;
+                            h.x.min(h.y).min(h.z) / 2.0 >= MIN_FRAGMENT_HALF_EXTENT
+                        })
+                        .unwrap_or(false);
+                    if breaks && big_enough {
+                        broken.insert(*e);
+                    }
+                }
+            }
+        }
+
+        for entity in broken {
+            let (body, block, kind, grabbable) = match (
+                physics_bodies.get(entity),
+                blocks.get(entity),
+                kinds.get(entity),
+                grabbables.get(entity),
+            ) {
+                (Some(a), Some(b), Some(c), Some(d)) => (a, b, c, d),
+                _ => continue,
+            };
+            let bh = match physics_world.collider_body_handle(body.0) {
+                Some(x) => x,
+                None => continue,
+            };
+            let (pos, velocity, center) = match physics_world.rigid_body(bh) {
+                Some(rb) => (*rb.position(), *rb.velocity(), rb.center_of_mass()),
+                None => continue,
+            };
+            physics_world.remove_bodies(&[bh]);
+            entities.delete(entity).unwrap();
+
+            let half_extents = block.half_extents / 2.0;
+            for i in 0..8 {
+                let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+                let offset = Vector3::new(
+                    sign(1) * half_extents.x,
+                    sign(2) * half_extents.y,
+                    sign(4) * half_extents.z,
+                );
+                let fragment_center = pos * Point3::new(offset.x, offset.y, offset.z);
+                let mut fragment_pos = pos;
+                fragment_pos.translation.vector = fragment_center.coords;
+                // a point of a rigid body moves with v + w x r
+                let linear = velocity.linear + velocity.angular.cross(&(fragment_center - center));
+                spawn_block(
+                    lazy.create_entity(&entities),
+                    &mut physics_world,
+                    block_assets.cube(),
+                    NewBlock {
+                        name: "fragment".to_owned(),
+                        half_extents,
+                        pos: fragment_pos,
+                        velocity: Velocity::new(linear, velocity.angular),
+                        kind: *kind,
+                        color: block.color,
+                        texture: block.texture.clone(),
+                        grabbable: grabbable.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<CollisionEvent>>()
+                .register_reader(),
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use super::hud::set_text;
+use crate::{
+    components::{Block, CameraSelf, PhysicsBody, Selected},
+    resources::{CameraMode, MyWorld, Picker, Resumed},
+};
+
+use amethyst::{
+    core::{nalgebra::Vector3, Transform},
+    ecs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage},
+    input::InputEvent,
+    renderer::{Camera, Hidden},
+    shrev::{EventChannel, ReaderId},
+    ui::{UiFinder, UiText},
+};
+
+use nphysics3d::object::Collider;
+use specs::{prelude::Resources, Entity};
+use winit::{Event, MouseScrollDelta, WindowEvent};
+
+const THIRD_PERSON_DISTANCE: f32 = 4.0;
+/// How far above the player's eyes the third person camera is.
+const THIRD_PERSON_RISE: f32 = 0.5;
+const ORBIT_DISTANCE: f32 = 6.0;
+const ORBIT_DISTANCE_RANGE: (f32, f32) = (1.5, 50.0);
+/// Each line the mouse wheel scrolls brings the orbit camera this much closer, as a factor.
+const ZOOM_STEP: f32 = 0.9;
+/// Pixels of a touchpad scroll that count as a line of the mouse wheel.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Where the player's eyes are, at the top of their collider.
+pub(super) fn player_eye(co: &Collider<f32>) -> Vector3<f32> {
+    let center = co.position().translation.vector;
+    let top = co.shape().aabb(co.position()).maxs().y;
+    Vector3::new(center.x, top, center.z)
+}
+
+/// Puts the camera where `CameraMode` says, at the player's eyes or around them, and shows the
+/// player's body when the camera is out of it. `next_camera` switches to the next mode and the
+/// mouse wheel zooms the orbit camera in and out.
+///
+/// The free fly camera is moved by `TranslationSystem` instead, and looking around is left to
+/// `RotationSystem` in every mode.
+pub struct CameraSystem {
+    input_reader: Option<ReaderId<InputEvent<String>>>,
+    event_reader: Option<ReaderId<Event>>,
+    orbit_distance: f32,
+    /// The block the orbit camera turns around while nothing is selected.
+    orbit_around: Option<Entity>,
+    /// Where the orbit camera turns around when it has neither a selection nor a block.
+    orbit_point: Vector3<f32>,
+}
+
+impl Default for CameraSystem {
+    fn default() -> Self {
+        CameraSystem {
+            input_reader: None,
+            event_reader: None,
+            orbit_distance: ORBIT_DISTANCE,
+            orbit_around: None,
+            orbit_point: Vector3::zeros(),
+        }
+    }
+}
+
+impl CameraSystem {
+    /// Picks what the orbit camera turns around if nothing gets selected: the pointed block,
+    /// else the pointed spot, else a spot in front of the camera.
+    fn aim_orbit(&mut self, picker: &Picker, blocks: &ReadStorage<Block>, camera: &Transform) {
+        self.orbit_around = picker
+            .hit
+            .and_then(|hit| hit.entity)
+            .filter(|e| blocks.contains(*e));
+        self.orbit_point = match picker.hit {
+            Some(hit) => hit.point.coords,
+            None => {
+                let forward = camera.isometry().rotation * -Vector3::z();
+                camera.translation() + forward * self.orbit_distance
+            }
+        };
+    }
+
+    /// The middle of the selected blocks if there are any, else the block or spot picked by
+    /// `aim_orbit`.
+    fn orbit_target(
+        &self,
+        entities: &Entities,
+        selected: &ReadStorage<Selected>,
+        transforms: &WriteStorage<Transform>,
+    ) -> Vector3<f32> {
+        let (sum, count) = (selected, transforms)
+            .join()
+            .fold((Vector3::zeros(), 0), |(sum, count), (_, t)| {
+                (sum + t.translation(), count + 1)
+            });
+        if count > 0 {
+            return sum / count as f32;
+        }
+        self.orbit_around
+            .filter(|e| entities.is_alive(*e))
+            .and_then(|e| transforms.get(e))
+            .map_or(self.orbit_point, |t| *t.translation())
+    }
+
+    fn zoom(&mut self, lines: f32) {
+        let (min, max) = ORBIT_DISTANCE_RANGE;
+        self.orbit_distance = (self.orbit_distance * ZOOM_STEP.powf(lines))
+            .max(min)
+            .min(max);
+    }
+}
+
+type CameraSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Read<'s, EventChannel<Event>>,
+    Write<'s, CameraMode>,
+    Read<'s, MyWorld>,
+    Read<'s, Picker>,
+    ReadStorage<'s, CameraSelf>,
+    ReadStorage<'s, PhysicsBody>,
+    ReadStorage<'s, Camera>,
+    ReadStorage<'s, Block>,
+    ReadStorage<'s, Selected>,
+    WriteStorage<'s, Transform>,
+    WriteStorage<'s, Hidden>,
+    UiFinder<'s>,
+    WriteStorage<'s, UiText>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for CameraSystem {
+    type SystemData = CameraSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            input_events,
+            events,
+            mut mode,
+            physics_world,
+            picker,
+            cameraself,
+            physics_bodies,
+            cameras,
+            blocks,
+            selected,
+            mut transforms,
+            mut hidden,
+            finder,
+            mut texts,
+            resumed,
+        ): Self::SystemData,
+    ) {
+        let camera = match (&entities, &cameras, &transforms).join().next() {
+            Some((e, _, _)) => e,
+            None => return,
+        };
+
+        for event in input_events
+            .read(
+                self.input_reader
+                    .as_mut()
+                    .expect("`CameraSystem::setup` was not called before `CameraSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            if let InputEvent::ActionPressed(ref action) = *event {
+                if action == "next_camera" {
+                    *mode = mode.next();
+                    if *mode == CameraMode::Orbit {
+                        self.aim_orbit(&picker, &blocks, transforms.get(camera).unwrap());
+                    }
+                }
+            }
+        }
+        for event in events
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`CameraSystem::setup` was not called before `CameraSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            if let Event::WindowEvent {
+                event: WindowEvent::MouseWheel { delta, .. },
+                ..
+            } = *event
+            {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 / PIXELS_PER_LINE,
+                };
+                if *mode == CameraMode::Orbit {
+                    self.zoom(lines);
+                }
+            }
+        }
+
+        let mut eye = None;
+        for (entity, body, _) in (&entities, &physics_bodies, &cameraself).join() {
+            let co = match physics_world.collider(body.0) {
+                Some(x) => x,
+                None => continue,
+            };
+            eye = Some(player_eye(co));
+            if let Some(t) = transforms.get_mut(entity) {
+                *t.translation_mut() = co.position().translation.vector;
+            }
+            if *mode == CameraMode::FirstPerson {
+                hidden.insert(entity, Hidden).unwrap();
+            } else {
+                hidden.remove(entity);
+            }
+        }
+
+        let target = self.orbit_target(&entities, &selected, &transforms);
+        let t = transforms.get_mut(camera).unwrap();
+        let forward = t.isometry().rotation * -Vector3::z();
+        let position = match (*mode, eye) {
+            (CameraMode::FirstPerson, Some(eye)) => eye,
+            (CameraMode::ThirdPerson, Some(eye)) => {
+                eye - forward * THIRD_PERSON_DISTANCE + Vector3::y() * THIRD_PERSON_RISE
+            }
+            (CameraMode::Orbit, _) => target - forward * self.orbit_distance,
+            _ => *t.translation(),
+        };
+        *t.translation_mut() = position;
+
+        let text = match *mode {
+            CameraMode::FirstPerson => String::new(),
+            CameraMode::Orbit => {
+                format!("Camera: {}, {:.1} away", mode.name(), self.orbit_distance)
+            }
+            _ => format!("Camera: {}", mode.name()),
+        };
+        set_text(&finder, &mut texts, "camera", text);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.input_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+        self.event_reader = Some(res.fetch_mut::<EventChannel<Event>>().register_reader());
+    }
+}
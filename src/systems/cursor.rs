@@ -0,0 +1,76 @@
+use super::hud::set_text;
+
+use crate::resources::Resumed;
+
+use amethyst::{
+    controls::{HideCursor, WindowFocus},
+    ecs::{Read, System, Write, WriteStorage},
+    input::InputEvent,
+    renderer::MouseButton,
+    shrev::{EventChannel, ReaderId},
+    ui::{UiFinder, UiText},
+};
+
+use specs::prelude::Resources;
+
+/// `toggle_cursor` releases the cursor, or captures it again, and a left click in the window
+/// captures it back. While it is released the camera stays still and the HUD says so.
+///
+/// `MoveSystem` doesn't take the click that captures the cursor as a grab.
+#[derive(Default)]
+pub struct CursorSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+type CursorSystemData<'s> = (
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Read<'s, WindowFocus>,
+    Write<'s, HideCursor>,
+    UiFinder<'s>,
+    WriteStorage<'s, UiText>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for CursorSystem {
+    type SystemData = CursorSystemData<'s>;
+
+    fn run(&mut self, (events, focus, mut hide, finder, mut texts, resumed): Self::SystemData) {
+        for event in events
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`CursorSystem::setup` was not called before `CursorSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            match *event {
+                InputEvent::ActionPressed(ref action) if action == "toggle_cursor" => {
+                    hide.hide = !hide.hide
+                }
+                InputEvent::MouseButtonPressed(MouseButton::Left)
+                    if !hide.hide && focus.is_focused =>
+                {
+                    hide.hide = true
+                }
+                _ => (),
+            }
+        }
+
+        let overlay = if hide.hide {
+            String::new()
+        } else {
+            "Click to resume".to_owned()
+        };
+        set_text(&finder, &mut texts, "cursor_released", overlay);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
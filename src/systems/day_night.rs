@@ -0,0 +1,89 @@
+use super::hud::set_text;
+use crate::resources::{Lighting, Resumed, TimeOfDay};
+
+use amethyst::{
+    core::timing::Time,
+    ecs::{Read, System, Write, WriteStorage},
+    input::{InputEvent, InputHandler},
+    renderer::SkyboxColor,
+    shrev::{EventChannel, ReaderId},
+    ui::{UiFinder, UiText},
+};
+
+use specs::prelude::Resources;
+
+/// Hours per second while `time_later` or `time_earlier` is held.
+const SCRUB_SPEED: f32 = 4.0;
+
+/// Runs the clock of `TimeOfDay` over `Sky::day_length` and colors the sky after it,
+/// `LightingSystem` moves the sun and fades the ambient light.
+///
+/// `pause_day` stops the clock, holding `time_later` or `time_earlier` moves it, paused or not.
+#[derive(Default)]
+pub struct DayNightSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+type DayNightSystemData<'s> = (
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Read<'s, InputHandler<String, String>>,
+    Read<'s, Time>,
+    Read<'s, Lighting>,
+    Write<'s, TimeOfDay>,
+    Write<'s, SkyboxColor>,
+    UiFinder<'s>,
+    WriteStorage<'s, UiText>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for DayNightSystem {
+    type SystemData = DayNightSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (events, input, time, lighting, mut time_of_day, mut skybox, finder, mut texts, resumed): Self::SystemData,
+    ) {
+        for event in events
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`DayNightSystem::setup` was not called before `DayNightSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            if let InputEvent::ActionPressed(ref action) = *event {
+                if action == "pause_day" {
+                    time_of_day.paused = !time_of_day.paused;
+                }
+            }
+        }
+
+        let is_down = |action: &str| input.action_is_down(action).unwrap_or(false);
+        let scrub = match (is_down("time_later"), is_down("time_earlier")) {
+            (true, false) => SCRUB_SPEED,
+            (false, true) => -SCRUB_SPEED,
+            _ => 0.0,
+        };
+        let speed = if time_of_day.paused || lighting.sky.day_length <= 0.0 {
+            scrub
+        } else {
+            scrub + 24.0 / lighting.sky.day_length
+        };
+        time_of_day.advance(speed * time.delta_seconds());
+
+        let (zenith, nadir) = lighting.sky.colors(time_of_day.daylight());
+        skybox.zenith = zenith;
+        skybox.nadir = nadir;
+        set_text(&finder, &mut texts, "time_of_day", time_of_day.describe());
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
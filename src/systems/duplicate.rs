@@ -0,0 +1,234 @@
+use crate::{
+    blocks::{spawn_block, NewBlock},
+    components::{Block, Grabbable, Held, MaterialKind, PhysicsBody, Selected},
+    resources::{BlockAssets, GrabRequest, MyWorld, Picker, Resumed, UiSound},
+};
+
+use amethyst::{
+    core::{nalgebra::Vector3, Named, Transform},
+    ecs::{Entities, Join, LazyUpdate, Read, ReadExpect, ReadStorage, System, Write},
+    input::InputEvent,
+    renderer::Camera,
+    shrev::{EventChannel, ReaderId},
+};
+
+use ncollide3d::{
+    shape::{Cuboid, Shape},
+    world::CollisionGroups,
+};
+use nphysics3d::math::Velocity;
+use specs::{prelude::Resources, Entity};
+
+const MAX_TOI_DUPLICATE: f32 = 4.0;
+/// Space between the camera and the copies, clear of the player's collider.
+const HOLD_CLEARANCE: f32 = 1.0;
+/// How many spots, each further from the camera than the last, are tried for the copies.
+const PLACEMENT_TRIES: usize = 4;
+
+/// Clones the pointed block, or every selected or held block when the pointed one is, and
+/// hands the copies to `MoveSystem` so they are held right away.
+///
+/// The copies are spawned in front of the camera, keeping their layout, at the distance they
+/// are then held at. Spots where they would overlap a body are skipped for one further away,
+/// and nothing is copied if none is free.
+#[derive(Default)]
+pub struct DuplicateSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+impl DuplicateSystem {
+    /// The pointed entity first, followed by the rest of the group it is part of.
+    fn originals(
+        &self,
+        pointed: Entity,
+        entities: &Entities,
+        selected: &ReadStorage<Selected>,
+        held: &ReadStorage<Held>,
+    ) -> Vec<Entity> {
+        let group: Vec<Entity> = if selected.contains(pointed) {
+            (entities, selected).join().map(|(e, _)| e).collect()
+        } else if held.contains(pointed) {
+            (entities, held).join().map(|(e, _)| e).collect()
+        } else {
+            vec![]
+        };
+        Some(pointed)
+            .into_iter()
+            .chain(group.into_iter().filter(|e| *e != pointed))
+            .collect()
+    }
+}
+
+type DuplicateSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Read<'s, Picker>,
+    ReadStorage<'s, Camera>,
+    ReadStorage<'s, Transform>,
+    Write<'s, MyWorld>,
+    ReadStorage<'s, PhysicsBody>,
+    ReadStorage<'s, Block>,
+    ReadStorage<'s, MaterialKind>,
+    ReadStorage<'s, Grabbable>,
+    ReadStorage<'s, Named>,
+    ReadStorage<'s, Selected>,
+    ReadStorage<'s, Held>,
+    ReadExpect<'s, BlockAssets>,
+    Read<'s, LazyUpdate>,
+    Write<'s, EventChannel<GrabRequest>>,
+    Write<'s, EventChannel<UiSound>>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for DuplicateSystem {
+    type SystemData = DuplicateSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            events,
+            picker,
+            cameras,
+            transforms,
+            mut physics_world,
+            physics_bodies,
+            blocks,
+            kinds,
+            grabbables,
+            names,
+            selected,
+            held,
+            block_assets,
+            lazy,
+            mut grab_requests,
+            mut ui_sounds,
+            resumed,
+        ): Self::SystemData,
+    ) {
+        let mut duplicate = false;
+        for event in
+            events
+                .read(self.event_reader.as_mut().expect(
+                    "`DuplicateSystem::setup` was not called before `DuplicateSystem::run`",
+                ))
+                .filter(|_| !resumed.0)
+        {
+            if let InputEvent::ActionPressed(ref action) = *event {
+                duplicate |= action == "duplicate";
+            }
+        }
+        let pointed = match picker
+            .entity_within(MAX_TOI_DUPLICATE)
+            .filter(|(e, _)| grabbables.contains(*e))
+        {
+            Some((e, _)) if duplicate => e,
+            _ => return,
+        };
+        let camera = match (&cameras, &transforms).join().next() {
+            Some((_, t)) => *t.isometry(),
+            None => return,
+        };
+        let forward = -(camera.rotation * Vector3::z());
+
+        let mut originals = vec![];
+        for entity in self.originals(pointed, &entities, &selected, &held) {
+            let parts = (
+                physics_bodies.get(entity),
+                blocks.get(entity),
+                kinds.get(entity),
+                grabbables.get(entity),
+            );
+            if let (Some(body), Some(block), Some(kind), Some(grabbable)) = parts {
+                if let Some(rb) = physics_world
+                    .collider_body_handle(body.0)
+                    .and_then(|bh| physics_world.rigid_body(bh))
+                {
+                    originals.push((entity, *rb.position(), block, *kind, grabbable));
+                }
+            }
+        }
+        if originals.is_empty() {
+            return;
+        }
+
+        // the copies are moved by the same offset, so the pointed one lands in front of the
+        // camera and the others keep their place around it
+        let origin = originals[0].1.translation.vector;
+        let group_radius = originals
+            .iter()
+            .map(|(_, pos, block, _, _)| {
+                (pos.translation.vector - origin).norm() + block.half_extents.norm()
+            })
+            .fold(0.0, |radius: f32, r| radius.max(r));
+        let is_free = |offset: &Vector3<f32>| {
+            originals.iter().all(|(_, pos, block, _, _)| {
+                let mut pos = *pos;
+                pos.translation.vector += offset;
+                let aabb = Cuboid::new(block.half_extents).aabb(&pos);
+                physics_world
+                    .collision_world()
+                    .interferences_with_aabb(&aabb, &CollisionGroups::new())
+                    .next()
+                    .is_none()
+            })
+        };
+        let placement = (0..PLACEMENT_TRIES)
+            .map(|i| group_radius * (1 + 2 * i) as f32 + HOLD_CLEARANCE)
+            .map(|distance| {
+                let target = camera.translation.vector + forward * distance;
+                (distance, target - origin)
+            })
+            .find(|(_, offset)| is_free(offset));
+        let (distance, offset) = match placement {
+            Some(x) => x,
+            None => {
+                info!("no room in front of the camera for the copy");
+                return;
+            }
+        };
+
+        let mut copies: Vec<Entity> = originals
+            .into_iter()
+            .map(|(entity, mut pos, block, kind, grabbable)| {
+                let name = names
+                    .get(entity)
+                    .map(|n| n.name.to_string())
+                    .unwrap_or_default();
+                pos.translation.vector += offset;
+                spawn_block(
+                    lazy.create_entity(&entities),
+                    &mut physics_world,
+                    block_assets.cube(),
+                    NewBlock {
+                        name,
+                        half_extents: block.half_extents,
+                        pos,
+                        velocity: Velocity::zero(),
+                        kind,
+                        color: block.color,
+                        texture: block.texture.clone(),
+                        grabbable: grabbable.clone(),
+                    },
+                )
+            })
+            .collect();
+        let anchor = copies.remove(0);
+        grab_requests.single_write(GrabRequest {
+            entity: anchor,
+            members: copies,
+            distance,
+        });
+        ui_sounds.single_write(UiSound::Spawn);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    components::{Grabbable, PhysicsBody},
+    resources::{MyWorld, PhysicsClock, PhysicsHistory, PhysicsStats, Picker},
+};
+
+use amethyst::{
+    core::Named,
+    ecs::{Read, ReadStorage, System, WriteStorage},
+    ui::{UiFinder, UiText},
+};
+
+use specs::Entity;
+
+pub(super) fn set_text(
+    finder: &UiFinder,
+    texts: &mut WriteStorage<UiText>,
+    id: &str,
+    text: String,
+) {
+    if let Some(ui_text) = finder.find(id).and_then(|e| texts.get_mut(e)) {
+        if ui_text.text != text {
+            ui_text.text = text;
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct HudSystem {
+    /// The pointed entity, the physics step and the size of its structure at that step, since
+    /// contacts only change when physics steps.
+    structure: Option<(Entity, u64, usize)>,
+}
+
+impl HudSystem {
+    fn structure_size(
+        &mut self,
+        physics_world: &MyWorld,
+        entity: Entity,
+        body: &PhysicsBody,
+        step: u64,
+    ) -> usize {
+        match self.structure {
+            Some((e, s, size)) if e == entity && s == step => size,
+            _ => {
+                let size = physics_world.connected_colliders(body.0).len();
+                self.structure = Some((entity, step, size));
+                size
+            }
+        }
+    }
+}
+
+type HudSystemData<'s> = (
+    UiFinder<'s>,
+    WriteStorage<'s, UiText>,
+    Read<'s, PhysicsClock>,
+    Read<'s, PhysicsHistory>,
+    Read<'s, PhysicsStats>,
+    Read<'s, Picker>,
+    Read<'s, MyWorld>,
+    ReadStorage<'s, PhysicsBody>,
+    ReadStorage<'s, Grabbable>,
+    ReadStorage<'s, Named>,
+);
+
+impl<'s> System<'s> for HudSystem {
+    type SystemData = HudSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            finder,
+            mut texts,
+            clock,
+            history,
+            stats,
+            picker,
+            physics_world,
+            physics_bodies,
+            grabbables,
+            names,
+        ): Self::SystemData,
+    ) {
+        let physics = if history.rewinding {
+            format!(
+                "{} (rewinding, {} steps left)",
+                clock.describe(),
+                history.len()
+            )
+        } else {
+            clock.describe()
+        };
+        set_text(&finder, &mut texts, "physics_clock", physics);
+        set_text(&finder, &mut texts, "physics_stats", stats.describe());
+
+        let pointed = picker
+            .hit
+            .and_then(|hit| hit.entity)
+            .filter(|e| grabbables.contains(*e))
+            .and_then(|e| physics_bodies.get(e).map(|body| (e, body)))
+            .map(|(e, body)| {
+                let name = names.get(e).map(|n| n.name.to_string()).unwrap_or_default();
+                let structure = self.structure_size(&physics_world, e, body, stats.steps);
+                format!("{}, in a structure of {} blocks", name, structure)
+            })
+            .unwrap_or_default();
+        set_text(&finder, &mut texts, "pointed", pointed);
+    }
+}
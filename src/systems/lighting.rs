@@ -0,0 +1,237 @@
+use super::hud::set_text;
+use crate::{
+    components::Block,
+    resources::{LightDesc, LightKind, Lighting, Picker, Resumed, TimeOfDay},
+};
+
+use amethyst::{
+    config::Config,
+    core::{nalgebra::Vector3, timing::Time, Transform},
+    ecs::{Entities, Read, ReadStorage, System, Write, WriteStorage},
+    input::{InputEvent, InputHandler},
+    renderer::{AmbientColor, Light},
+    shrev::{EventChannel, ReaderId},
+    ui::{UiFinder, UiText},
+    utils::application_root_dir,
+};
+
+use specs::{prelude::Resources, Entity};
+
+const MAX_TOI_LAMP: f32 = 4.0;
+/// While a light is brightened or dimmed its intensity changes by a factor of e every this many
+/// seconds.
+const INTENSITY_SECONDS: f32 = 1.0;
+
+/// Creates an entity for each light of `Lighting` and keeps them and the ambient color in sync
+/// with it, turning the sun and fading the ambient light with `TimeOfDay`.
+///
+/// `toggle_lighting` opens a panel on the HUD where `next_light` goes through the ambient light
+/// and the rig, `light_brighter` and `light_dimmer` change the intensity of the chosen one and
+/// `save_lighting` writes them all back to `lighting.ron`. `toggle_lamp` makes the pointed block
+/// a lamp, or a plain block again.
+#[derive(Default)]
+pub struct LightingSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+    /// The entity of each of `Lighting::lights`.
+    rig: Vec<Entity>,
+    /// Whether the rig matches `Lighting`, the sun and the ambient light follow the time of day
+    /// every frame regardless.
+    synced: bool,
+    open: bool,
+    /// The light the panel adjusts, 0 is the ambient light and the rig follows from 1.
+    chosen: usize,
+}
+
+impl LightingSystem {
+    /// Matches the rig to `Lighting`, all but the sun, which `follow_day` turns.
+    fn sync(
+        &mut self,
+        lighting: &Lighting,
+        entities: &Entities,
+        lights: &mut WriteStorage<Light>,
+        transforms: &mut WriteStorage<Transform>,
+    ) {
+        for entity in self
+            .rig
+            .split_off(lighting.lights.len().min(self.rig.len()))
+        {
+            entities.delete(entity).unwrap();
+        }
+        while self.rig.len() < lighting.lights.len() {
+            let [x, y, z] = lighting.lights[self.rig.len()].position();
+            let mut t = Transform::default();
+            *t.translation_mut() = Vector3::new(x, y, z);
+            let entity = entities.create();
+            transforms.insert(entity, t).unwrap();
+            self.rig.push(entity);
+        }
+        for (i, (entity, desc)) in self.rig.iter().zip(&lighting.lights).enumerate() {
+            if lighting.sky.sun != Some(i) {
+                lights.insert(*entity, desc.light()).unwrap();
+            }
+        }
+        self.synced = true;
+    }
+
+    /// Turns the sun and fades the ambient light with `TimeOfDay`.
+    fn follow_day(
+        &self,
+        lighting: &Lighting,
+        time_of_day: &TimeOfDay,
+        ambient: &mut AmbientColor,
+        lights: &mut WriteStorage<Light>,
+    ) {
+        let daylight = time_of_day.daylight();
+        let night = lighting.sky.night_ambient;
+        let mut ambient_light = lighting.ambient.clone();
+        ambient_light.intensity *= night + (1.0 - night) * daylight;
+        ambient.0 = ambient_light.rgba();
+
+        let sun = lighting
+            .sky
+            .sun
+            .and_then(|i| Some((self.rig.get(i)?, lighting.lights.get(i)?)));
+        if let Some((entity, desc)) = sun {
+            let light = LightDesc {
+                kind: LightKind::Directional {
+                    direction: time_of_day.sun_direction(),
+                },
+                intensity: desc.intensity * daylight,
+                ..desc.clone()
+            }
+            .light();
+            lights.insert(*entity, light).unwrap();
+        }
+    }
+
+    fn describe(&self, lighting: &Lighting) -> String {
+        if !self.open {
+            return String::new();
+        }
+        let light = match self.chosen.checked_sub(1) {
+            None => format!("ambient light, intensity {:.2}", lighting.ambient.intensity),
+            Some(i) => lighting.lights[i].describe(),
+        };
+        format!(
+            "Lighting {}/{}: {}",
+            self.chosen + 1,
+            lighting.lights.len() + 1,
+            light
+        )
+    }
+}
+
+type LightingSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Read<'s, InputHandler<String, String>>,
+    Read<'s, Time>,
+    Read<'s, Picker>,
+    Write<'s, Lighting>,
+    Read<'s, TimeOfDay>,
+    Write<'s, AmbientColor>,
+    ReadStorage<'s, Block>,
+    WriteStorage<'s, Light>,
+    WriteStorage<'s, Transform>,
+    UiFinder<'s>,
+    WriteStorage<'s, UiText>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for LightingSystem {
+    type SystemData = LightingSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            events,
+            input,
+            time,
+            picker,
+            mut lighting,
+            time_of_day,
+            mut ambient,
+            blocks,
+            mut lights,
+            mut transforms,
+            finder,
+            mut texts,
+            resumed,
+        ): Self::SystemData,
+    ) {
+        for event in events
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`LightingSystem::setup` was not called before `LightingSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            let action = match *event {
+                InputEvent::ActionPressed(ref action) => action.as_str(),
+                _ => continue,
+            };
+            match action {
+                "toggle_lighting" => self.open = !self.open,
+                "next_light" if self.open => {
+                    self.chosen = (self.chosen + 1) % (lighting.lights.len() + 1)
+                }
+                "save_lighting" if self.open => {
+                    let path = format!("{}/resources/lighting.ron", application_root_dir());
+                    match lighting.write(&path) {
+                        Ok(()) => info!("saved the lighting to {}", path),
+                        Err(e) => error!("couldn't save the lighting to {}: {}", path, e),
+                    }
+                }
+                "toggle_lamp" => {
+                    let pointed = picker
+                        .entity_within(MAX_TOI_LAMP)
+                        .map(|(e, _)| e)
+                        .filter(|e| blocks.contains(*e));
+                    if let Some(entity) = pointed {
+                        if lights.contains(entity) {
+                            lights.remove(entity);
+                        } else {
+                            lights.insert(entity, lighting.lamp.light()).unwrap();
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let is_down = |action: &str| input.action_is_down(action).unwrap_or(false);
+        let direction = match (is_down("light_brighter"), is_down("light_dimmer")) {
+            (true, false) => 1,
+            (false, true) => -1,
+            _ => 0,
+        };
+        // the rig may have been replaced since a light was chosen
+        self.chosen %= lighting.lights.len() + 1;
+        if self.open && direction != 0 {
+            let factor = (direction as f32 * time.delta_seconds() / INTENSITY_SECONDS).exp();
+            match self.chosen.checked_sub(1) {
+                None => lighting.ambient.intensity *= factor,
+                Some(i) => lighting.lights[i].intensity *= factor,
+            }
+            self.synced = false;
+        }
+
+        if !self.synced {
+            self.sync(&lighting, &entities, &mut lights, &mut transforms);
+        }
+        self.follow_day(&lighting, &time_of_day, &mut ambient, &mut lights);
+        set_text(&finder, &mut texts, "lighting", self.describe(&lighting));
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
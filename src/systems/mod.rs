@@ -1,9 +1,47 @@
-mod physics;
+mod breakage;
+mod camera;
+mod cursor;
+mod day_night;
+mod duplicate;
+mod hud;
+mod lighting;
 mod moving;
+mod paint;
+mod patterns;
+mod physics;
+mod physics_control;
+mod pick;
+mod projection;
+mod pulse;
+mod resize;
 mod rotation;
+mod selection;
+mod selection_actions;
+mod sound;
+mod stress_view;
+mod texture;
 mod translation;
 
-pub use self::physics::PhysicsSystem;
+pub use self::breakage::BreakageSystem;
+pub use self::camera::CameraSystem;
+pub use self::cursor::CursorSystem;
+pub use self::day_night::DayNightSystem;
+pub use self::duplicate::DuplicateSystem;
+pub use self::hud::HudSystem;
+pub use self::lighting::LightingSystem;
 pub use self::moving::MoveSystem;
+pub use self::paint::PaintSystem;
+pub use self::patterns::PatternSystem;
+pub use self::physics::PhysicsSystem;
+pub use self::physics_control::PhysicsControlSystem;
+pub use self::pick::PickSystem;
+pub use self::projection::ProjectionSystem;
+pub use self::pulse::PulseSystem;
+pub use self::resize::ResizeSystem;
 pub use self::rotation::RotationSystem;
+pub use self::selection::SelectionSystem;
+pub use self::selection_actions::SelectionActionsSystem;
+pub use self::sound::SoundSystem;
+pub use self::stress_view::StressViewSystem;
+pub use self::texture::TextureSystem;
 pub use self::translation::TranslationSystem;
@@ -1,26 +1,28 @@
-use std::{cmp::Ordering, f32};
+use std::f32;
 
 use crate::{
-    components::{Grabbable, PhysicsBody},
-    resources::MyWorld,
+    blocks::GRID_STEP,
+    components::{Grabbable, Held, Locked, PhysicsBody, Selected},
+    resources::{CameraMode, GrabRequest, MyWorld, Picker, Resumed, Settings, UiSound},
 };
 
 use amethyst::{
+    controls::HideCursor,
     core::{nalgebra::Vector3, timing::Time, Transform},
-    ecs::{Join, Read, ReadStorage, System, Write, WriteStorage},
+    ecs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage},
     input::InputHandler,
-    renderer::{Camera, Material, MouseButton},
+    renderer::{Camera, Material},
     shrev::{EventChannel, ReaderId},
 };
 
-use na::{Isometry3, Point3, UnitQuaternion, Vector3 as PhysicsVector3};
+use na::{Isometry3, UnitQuaternion};
 
-use ncollide3d::query::{Ray, RayCast};
 use nphysics3d::{
     force_generator::{ConstantAcceleration, ForceGeneratorHandle},
+    math::Velocity,
     object::RigidBody,
 };
-use specs::{prelude::Resources, Entities, Entity};
+use specs::{prelude::Resources, Entity};
 use winit::{DeviceEvent, Event};
 
 const MAGIC_ANGULAR_VELOCITY_MULTIPLIER: f32 = 50.0;
@@ -28,6 +30,8 @@ const MAX_TOI_GRAB: f32 = 4.0;
 
 struct SelectedObject {
     entity: Entity,
+    /// Other entities carried along, with their pose relative to `entity`.
+    members: Vec<(Entity, Isometry3<f32>)>,
     previous_camera_position: Isometry3<f32>,
     force: ForceGeneratorHandle,
     distance: f32,
@@ -35,57 +39,77 @@ struct SelectedObject {
     box_up: Vector3<f32>,
 }
 
+/// Marks `entities` as held or not, showing them highlighted while held or selected.
+fn mark_held(
+    entities: &Entities,
+    list: &[Entity],
+    is_held: bool,
+    held: &mut WriteStorage<Held>,
+    selected: &ReadStorage<Selected>,
+    grabbables: &ReadStorage<Grabbable>,
+    materials: &mut WriteStorage<Material>,
+) {
+    // entities deleted while held are skipped
+    for entity in list.iter().filter(|e| entities.is_alive(**e)) {
+        if is_held {
+            held.insert(*entity, Held).unwrap();
+        } else {
+            held.remove(*entity);
+        }
+        if let Some(g) = grabbables.get(*entity) {
+            materials
+                .insert(*entity, g.material(is_held || selected.contains(*entity)))
+                .unwrap();
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct MoveSystem {
     selected_object: Option<SelectedObject>,
     did_release_click: bool,
     event_reader: Option<ReaderId<Event>>,
+    grab_reader: Option<ReaderId<GrabRequest>>,
+    /// The camera mode of the last frame.
+    camera_mode: CameraMode,
 }
 
 impl MoveSystem {
-    fn find_current_ray(
+    fn camera_isometry(
         &self,
         cameras: &ReadStorage<Camera>,
         transforms: &ReadStorage<Transform>,
-    ) -> (Ray<f32>, Isometry3<f32>) {
-        let isometry = (cameras, transforms).join().next().unwrap().1.isometry();
-        let r = isometry.rotation * Vector3::z();
-        (
-            Ray::new(
-                Point3::new(
-                    isometry.translation.vector.x,
-                    isometry.translation.vector.y,
-                    isometry.translation.vector.z,
-                ),
-                PhysicsVector3::new(-r.x, -r.y, -r.z),
-            ),
-            *isometry,
-        )
+    ) -> Isometry3<f32> {
+        *(cameras, transforms).join().next().unwrap().1.isometry()
     }
 
-    fn find_pointed_object<'a>(
-        &self,
-        ray: &Ray<f32>,
-        entities: &Entities,
-        physics_world: &Write<MyWorld>,
-        physics_bodies: &WriteStorage<PhysicsBody>,
-        grabbables: &'a ReadStorage<Grabbable>,
-    ) -> Option<(Entity, &'a Grabbable, f32)> {
-        (entities, physics_bodies, grabbables)
-            .join()
-            .flat_map(|(e, b, g)| {
-                let co = physics_world
-                    .collision_world()
-                    .collision_object(b.0)
-                    .unwrap();
-                co.shape()
-                    .toi_with_ray(co.position(), &ray, true)
-                    .map(|x| (e, b, x, g))
-            })
-            .min_by(|(_, _, toi1, _), (_, _, toi2, _)| {
-                toi1.partial_cmp(&toi2).unwrap_or(Ordering::Equal)
+    fn held_entities(&self) -> Vec<Entity> {
+        self.selected_object
+            .iter()
+            .flat_map(|so| {
+                Some(so.entity)
+                    .into_iter()
+                    .chain(so.members.iter().map(|(e, _)| *e))
             })
-            .map(|(e, _, toi, g)| (e, g, toi))
+            .collect()
+    }
+
+    fn anti_gravity(
+        physics_world: &mut Write<MyWorld>,
+        physics_bodies: &WriteStorage<PhysicsBody>,
+        entities: &[Entity],
+    ) -> ForceGeneratorHandle {
+        let mut f =
+            ConstantAcceleration::new(-physics_world.gravity(), Vector3::new(0.0, 0.0, 0.0));
+        for entity in entities {
+            if let Some(bh) = physics_bodies
+                .get(*entity)
+                .and_then(|body| physics_world.collider_body_handle(body.0))
+            {
+                f.add_body_part(bh);
+            }
+        }
+        physics_world.add_force_generator(f)
     }
 
     fn get_selected_object_rigid_body<'a>(
@@ -120,7 +144,7 @@ impl MoveSystem {
         world: &mut Write<MyWorld>,
         time: &Read<Time>,
     ) {
-        let camera_isometry = self.find_current_ray(cameras, transforms).1;
+        let camera_isometry = self.camera_isometry(cameras, transforms);
         let rb = match self.get_selected_object_rigid_body_mut(physics_bodies, world) {
             Some(x) => x,
             None => return,
@@ -135,40 +159,54 @@ impl MoveSystem {
             .cross(&(camera_isometry.rotation * Vector3::z()))
             + (rb.position().rotation * so.box_up)
                 .cross(&(camera_isometry.rotation * Vector3::y()));
-        rb.set_linear_velocity(linear / time.delta_seconds());
-        rb.set_angular_velocity(angular * MAGIC_ANGULAR_VELOCITY_MULTIPLIER);
+        let linear = linear / time.delta_seconds();
+        let angular = angular * MAGIC_ANGULAR_VELOCITY_MULTIPLIER;
+        rb.set_linear_velocity(linear);
+        rb.set_angular_velocity(angular);
         so.previous_camera_position = camera_isometry;
+
+        // the rest of the structure follows rigidly, correcting any drift from its offset
+        let anchor = *rb.position();
+        let anchor_center = rb.center_of_mass();
+        for (entity, offset) in so.members.iter() {
+            let bh = match physics_bodies
+                .get(*entity)
+                .and_then(|body| world.collider_body_handle(body.0))
+            {
+                Some(x) => x,
+                None => continue,
+            };
+            let rb = match world.rigid_body_mut(bh) {
+                Some(x) => x,
+                None => continue,
+            };
+            let target = anchor * offset;
+            let drift = (target.translation.vector - rb.position().translation.vector)
+                / time.delta_seconds();
+            let twist = (target.rotation * rb.position().rotation.inverse()).scaled_axis()
+                / time.delta_seconds();
+            rb.set_linear_velocity(
+                linear + angular.cross(&(rb.center_of_mass() - anchor_center)) + drift,
+            );
+            rb.set_angular_velocity(angular + twist);
+        }
     }
 
     fn grab_object(
         &mut self,
-        entities: &Entities,
-        cameras: &ReadStorage<Camera>,
+        target: Option<(Entity, f32)>,
+        camera_isometry: Isometry3<f32>,
         physics_world: &mut Write<MyWorld>,
-        transforms: &ReadStorage<Transform>,
         physics_bodies: &WriteStorage<PhysicsBody>,
         grabbables: &ReadStorage<Grabbable>,
-        materials: &mut WriteStorage<Material>,
     ) {
-        let (ray, camera_isometry) = self.find_current_ray(&cameras, &transforms);
-
-        self.selected_object = self
-            .find_pointed_object(&ray, entities, &physics_world, physics_bodies, grabbables)
-            .filter(|(_, _, toi)| *toi < MAX_TOI_GRAB)
-            .map(|(entity, g, toi)| {
-                let mut f = ConstantAcceleration::new(
-                    -physics_world.gravity(),
-                    Vector3::new(0.0, 0.0, 0.0),
-                );
-                // this is awful
-                f.add_body_part(
-                    physics_world
-                        .collider_body_handle(physics_bodies.get(entity).unwrap().0)
-                        .unwrap(),
-                );
-                (entity, physics_world.add_force_generator(f), toi, g)
+        self.selected_object = target
+            .filter(|(entity, _)| grabbables.get(*entity).is_some())
+            .map(|(entity, distance)| {
+                let antig = Self::anti_gravity(physics_world, physics_bodies, &[entity]);
+                (entity, antig, distance)
             })
-            .map(|(entity, antig, toi, g)| {
+            .map(|(entity, antig, toi)| {
                 let rot_inv = physics_world
                     .rigid_body(
                         physics_world
@@ -179,11 +217,9 @@ impl MoveSystem {
                     .position()
                     .rotation
                     .inverse();
-                materials
-                    .insert(entity, g.selected_material.clone())
-                    .unwrap();
                 SelectedObject {
                     entity,
+                    members: vec![],
                     previous_camera_position: camera_isometry,
                     force: antig,
                     distance: toi,
@@ -193,24 +229,116 @@ impl MoveSystem {
             });
     }
 
-    fn drop_object(
+    /// Carries `members` along with the grabbed object, keeping their current pose relative
+    /// to it.
+    fn carry(
         &mut self,
+        members: Vec<Entity>,
         physics_world: &mut Write<MyWorld>,
+        physics_bodies: &WriteStorage<PhysicsBody>,
+    ) {
+        let anchor = match self.get_selected_object_rigid_body(physics_bodies, physics_world) {
+            Some(rb) => *rb.position(),
+            None => return,
+        };
+        let so = self.selected_object.as_mut().unwrap();
+        let anchor_entity = so.entity;
+        so.members = members
+            .into_iter()
+            .filter(|e| *e != anchor_entity)
+            .flat_map(|e| {
+                physics_bodies
+                    .get(e)
+                    .and_then(|body| physics_world.collider_body_handle(body.0))
+                    .and_then(|bh| physics_world.rigid_body(bh))
+                    .map(|rb| (e, anchor.inverse() * rb.position()))
+            })
+            .collect();
+        physics_world.remove_force_generator(so.force);
+        let held = self.held_entities();
+        let force = Self::anti_gravity(physics_world, physics_bodies, &held);
+        self.selected_object.as_mut().unwrap().force = force;
+    }
+
+    /// The blocks connected to the grabbed object, so the whole structure moves together.
+    fn connected_structure(
+        &self,
+        entities: &Entities,
+        physics_world: &MyWorld,
+        physics_bodies: &WriteStorage<PhysicsBody>,
         grabbables: &ReadStorage<Grabbable>,
-        materials: &mut WriteStorage<Material>,
+    ) -> Vec<Entity> {
+        let anchor = match self
+            .selected_object
+            .as_ref()
+            .and_then(|so| physics_bodies.get(so.entity))
+        {
+            Some(body) => body.0,
+            None => return vec![],
+        };
+        let colliders = physics_world.connected_colliders(anchor);
+        (entities, physics_bodies, grabbables)
+            .join()
+            .filter(|(_, body, _)| colliders.contains(&body.0))
+            .map(|(e, _, _)| e)
+            .collect()
+    }
+
+    /// Moves the held blocks across the ground so the grabbed one sits on the grid, keeping
+    /// their layout, gravity settles them vertically.
+    fn snap_to_grid(
+        &self,
+        physics_bodies: &WriteStorage<PhysicsBody>,
+        physics_world: &mut Write<MyWorld>,
     ) {
+        let anchor = match self.get_selected_object_rigid_body(physics_bodies, physics_world) {
+            Some(rb) => rb.position().translation.vector,
+            None => return,
+        };
+        let snap = |x: f32| (x / GRID_STEP).round() * GRID_STEP - x;
+        let offset = Vector3::new(snap(anchor.x), 0.0, snap(anchor.z));
+        for entity in self.held_entities() {
+            if let Some(rb) = physics_bodies
+                .get(entity)
+                .and_then(|body| physics_world.collider_body_handle(body.0))
+                .and_then(|bh| physics_world.rigid_body_mut(bh))
+            {
+                let mut pos = *rb.position();
+                pos.translation.vector += offset;
+                rb.set_position(pos);
+                rb.set_velocity(Velocity::zero());
+            }
+        }
+    }
+
+    fn drop_object(&mut self, physics_world: &mut Write<MyWorld>) {
         if let Some(ref so) = self.selected_object {
             physics_world.remove_force_generator(so.force);
-            materials
-                .insert(
-                    so.entity,
-                    grabbables.get(so.entity).unwrap().default_material.clone(),
-                )
-                .unwrap();
         }
         self.selected_object = None;
     }
 
+    /// Lets go of the selected object if its entity was deleted, e.g. when it shattered, and
+    /// stops carrying deleted members.
+    fn forget_deleted_object(
+        &mut self,
+        entities: &Entities,
+        physics_world: &mut Write<MyWorld>,
+        physics_bodies: &WriteStorage<PhysicsBody>,
+    ) {
+        let anchor_alive = match self.selected_object {
+            Some(ref mut so) => {
+                so.members
+                    .retain(|(e, _)| entities.is_alive(*e) && physics_bodies.get(*e).is_some());
+                entities.is_alive(so.entity) && physics_bodies.get(so.entity).is_some()
+            }
+            None => return,
+        };
+        if !anchor_alive {
+            self.drop_object(physics_world);
+        }
+    }
+
     fn rotate_selected_object<'a>(
         &mut self,
         physics_bodies: &WriteStorage<PhysicsBody>,
@@ -244,6 +372,7 @@ impl MoveSystem {
 
 type MoveSystemData<'s> = (
     Entities<'s>,
+    Read<'s, Picker>,
     ReadStorage<'s, Camera>,
     Write<'s, MyWorld>,
     ReadStorage<'s, Transform>,
@@ -252,7 +381,16 @@ type MoveSystemData<'s> = (
     ReadStorage<'s, Grabbable>,
     Read<'s, Time>,
     Read<'s, EventChannel<Event>>,
+    Read<'s, EventChannel<GrabRequest>>,
     WriteStorage<'s, Material>,
+    Write<'s, EventChannel<UiSound>>,
+    ReadStorage<'s, Selected>,
+    WriteStorage<'s, Held>,
+    ReadStorage<'s, Locked>,
+    Read<'s, HideCursor>,
+    Read<'s, Settings>,
+    Read<'s, Resumed>,
+    Read<'s, CameraMode>,
 );
 
 impl<'s> System<'s> for MoveSystem {
@@ -261,6 +399,7 @@ impl<'s> System<'s> for MoveSystem {
         &mut self,
         (
             entities,
+            picker,
             cameras,
             mut physics_world,
             transforms,
@@ -269,17 +408,89 @@ impl<'s> System<'s> for MoveSystem {
             grabbables,
             time,
             events,
+            grab_requests,
             mut materials,
+            mut ui_sounds,
+            selected,
+            mut held,
+            locked,
+            hide,
+            settings,
+            resumed,
+            camera_mode,
         ): Self::SystemData,
     ) {
+        let was_held = self.held_entities();
+        self.forget_deleted_object(&entities, &mut physics_world, &physics_bodies);
+        if self.selected_object.is_none() {
+            mark_held(
+                &entities,
+                &was_held,
+                false,
+                &mut held,
+                &selected,
+                &grabbables,
+                &mut materials,
+            );
+        }
         let camera_isometry = (&cameras, &transforms).join().next().unwrap().1.isometry();
-        for event in events.read(
-            &mut self
-                .event_reader
-                .as_mut()
-                .expect("`MoveSystem::setup` was not called before `MoveSystem::run`"),
-        ) {
-            if input.mouse_button_is_down(MouseButton::Right) {
+        // switching cameras makes the camera jump, which would throw the held block as if the
+        // camera had moved that far in a frame
+        if *camera_mode != self.camera_mode {
+            self.camera_mode = *camera_mode;
+            if let Some(so) = self.selected_object.as_mut() {
+                so.previous_camera_position = *camera_isometry;
+            }
+        }
+        let request = grab_requests
+            .read(
+                self.grab_reader
+                    .as_mut()
+                    .expect("`MoveSystem::setup` was not called before `MoveSystem::run`"),
+            )
+            .last()
+            .cloned();
+        if let Some(request) = request {
+            mark_held(
+                &entities,
+                &self.held_entities(),
+                false,
+                &mut held,
+                &selected,
+                &grabbables,
+                &mut materials,
+            );
+            self.drop_object(&mut physics_world);
+            self.grab_object(
+                Some((request.entity, request.distance)),
+                *camera_isometry,
+                &mut physics_world,
+                &physics_bodies,
+                &grabbables,
+            );
+            self.carry(request.members, &mut physics_world, &physics_bodies);
+            mark_held(
+                &entities,
+                &self.held_entities(),
+                true,
+                &mut held,
+                &selected,
+                &grabbables,
+                &mut materials,
+            );
+            // a click that is still down keeps holding instead of dropping the copy
+            self.did_release_click = false;
+        }
+        for event in events
+            .read(
+                &mut self
+                    .event_reader
+                    .as_mut()
+                    .expect("`MoveSystem::setup` was not called before `MoveSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            if input.action_is_down("rotate_block").unwrap_or(false) {
                 if let Event::DeviceEvent { ref event, .. } = *event {
                     if let DeviceEvent::MouseMotion { delta: (x, y) } = *event {
                         self.rotate_selected_object(
@@ -293,9 +504,13 @@ impl<'s> System<'s> for MoveSystem {
                 }
             }
         }
-        let is_left_click = input.mouse_button_is_down(MouseButton::Left);
+        // clicks with `select_modifier` held edit the selection instead of grabbing, and clicks on
+        // menus while the cursor is shown don't grab at all
+        let is_grab_click = input.action_is_down("grab").unwrap_or(false)
+            && !input.action_is_down("select_modifier").unwrap_or(false)
+            && hide.hide;
         match (
-            is_left_click,
+            is_grab_click,
             self.selected_object.is_some(),
             self.did_release_click,
         ) {
@@ -307,27 +522,76 @@ impl<'s> System<'s> for MoveSystem {
                     &mut physics_world,
                     &time,
                 );
-                self.did_release_click = !is_left_click;
+                self.did_release_click = !is_grab_click;
             }
             (true, false, true) => {
                 self.did_release_click = false;
                 self.grab_object(
-                    &entities,
-                    &cameras,
+                    picker
+                        .entity_within(MAX_TOI_GRAB)
+                        .filter(|(entity, _)| !locked.contains(*entity))
+                        .map(|(entity, hit)| (entity, hit.toi)),
+                    *camera_isometry,
                     &mut physics_world,
-                    &transforms,
                     &physics_bodies,
                     &grabbables,
-                    &mut materials,
                 );
+                let anchor = self.selected_object.as_ref().map(|so| so.entity);
+                if let Some(anchor) = anchor {
+                    // grabbing a selected block carries the whole selection
+                    let members = if selected.contains(anchor) {
+                        (&entities, &selected, !&locked)
+                            .join()
+                            .map(|(e, _, _)| e)
+                            .collect()
+                    } else if input.action_is_down("grab_structure").unwrap_or(false) {
+                        self.connected_structure(
+                            &entities,
+                            &physics_world,
+                            &physics_bodies,
+                            &grabbables,
+                        )
+                    } else {
+                        vec![]
+                    };
+                    self.carry(members, &mut physics_world, &physics_bodies);
+                    mark_held(
+                        &entities,
+                        &self.held_entities(),
+                        true,
+                        &mut held,
+                        &selected,
+                        &grabbables,
+                        &mut materials,
+                    );
+                    ui_sounds.single_write(UiSound::Grab);
+                }
             }
             (true, true, true) => {
                 self.did_release_click = false;
-                self.drop_object(&mut physics_world, &grabbables, &mut materials);
+                mark_held(
+                    &entities,
+                    &self.held_entities(),
+                    false,
+                    &mut held,
+                    &selected,
+                    &grabbables,
+                    &mut materials,
+                );
+                if settings.snap {
+                    self.snap_to_grid(&physics_bodies, &mut physics_world);
+                }
+                self.drop_object(&mut physics_world);
+                ui_sounds.single_write(UiSound::Drop);
             }
             (true, false, false) => (),
             (false, false, _) => self.did_release_click = true,
         }
+        // a click that starts while the cursor is shown, like the one capturing it back, has to
+        // be released before the next one grabs or drops
+        if !hide.hide {
+            self.did_release_click = false;
+        }
     }
 
     fn setup(&mut self, res: &mut Resources) {
@@ -335,5 +599,9 @@ impl<'s> System<'s> for MoveSystem {
 
         Self::SystemData::setup(res);
         self.event_reader = Some(res.fetch_mut::<EventChannel<Event>>().register_reader());
+        self.grab_reader = Some(
+            res.fetch_mut::<EventChannel<GrabRequest>>()
+                .register_reader(),
+        );
     }
 }
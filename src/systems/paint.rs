@@ -0,0 +1,277 @@
+use super::hud::set_text;
+use crate::{
+    components::{Block, Grabbable, Held, MaterialKind, Selected},
+    resources::{BlockAssets, Paint, Palettes, Picker, Resumed, Surface, TEXTURES},
+};
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    core::timing::Time,
+    ecs::{
+        Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage,
+    },
+    input::{InputEvent, InputHandler},
+    renderer::{Material, MaterialDefaults, Texture},
+    shrev::{EventChannel, ReaderId},
+    ui::{UiFinder, UiImage, UiText, UiTransform},
+};
+
+use specs::{prelude::Resources, Entity};
+
+const MAX_TOI_PAINT: f32 = 4.0;
+/// Degrees per second while a hue key is held.
+const HUE_SPEED: f32 = 90.0;
+/// Saturation and value change per second while their key is held.
+const CHANNEL_SPEED: f32 = 0.5;
+/// Swatch images in `hud.ron`, named `swatch_0` and onwards.
+const SWATCHES: usize = 8;
+const SWATCH_SIZE: f32 = 20.0;
+
+/// Paints blocks with the color picked on the HUD: the arrow and page keys adjust its hue,
+/// saturation and value, the number keys pick a swatch from the current palette. Painting
+/// removes a block's texture, `next_texture` cycles through them instead.
+#[derive(Default)]
+pub struct PaintSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+    /// The color the HUD last showed.
+    shown_color: Option<[f32; 4]>,
+    /// The palette and swatches the HUD last showed.
+    shown_swatches: Option<(usize, Vec<[f32; 4]>)>,
+}
+
+impl PaintSystem {
+    /// The pointed block, or every selected block if it is selected.
+    fn targets(
+        pointed: Entity,
+        entities: &Entities,
+        selected: &ReadStorage<Selected>,
+    ) -> Vec<Entity> {
+        if selected.contains(pointed) {
+            (entities, selected).join().map(|(e, _)| e).collect()
+        } else {
+            vec![pointed]
+        }
+    }
+
+    fn show(
+        &mut self,
+        paint: &Paint,
+        palettes: &Palettes,
+        block_assets: &mut BlockAssets,
+        loader: &Loader,
+        tex_storage: &AssetStorage<Texture>,
+        ui: &mut PaintUi,
+    ) {
+        let (ref finder, ref mut texts, ref mut images, ref mut ui_transforms) = *ui;
+        let mut set_color = |id: &str, color: [f32; 4]| {
+            if let Some(image) = finder.find(id).and_then(|e| images.get_mut(e)) {
+                image.texture = block_assets.texture(&Surface::color(color), loader, tex_storage);
+            }
+        };
+
+        let (_, swatches) = paint.swatches(palettes);
+        let color_changed = self.shown_color != Some(paint.color());
+        let swatches_changed = self
+            .shown_swatches
+            .as_ref()
+            .map_or(true, |(palette, shown)| {
+                *palette != paint.palette || shown.as_slice() != swatches
+            });
+        if color_changed || swatches_changed {
+            // the description names the palette too
+            set_text(finder, texts, "paint", paint.describe(palettes));
+        }
+        if color_changed {
+            self.shown_color = Some(paint.color());
+            set_color("paint_color", paint.color());
+        }
+        if !swatches_changed {
+            return;
+        }
+        self.shown_swatches = Some((paint.palette, swatches.to_vec()));
+        for i in 0..SWATCHES {
+            let id = format!("swatch_{}", i);
+            if let Some(color) = swatches.get(i) {
+                set_color(&id, *color);
+            }
+            // palettes with fewer colors hide the remaining swatches
+            if let Some(t) = finder.find(&id).and_then(|e| ui_transforms.get_mut(e)) {
+                let size = if i < swatches.len() { SWATCH_SIZE } else { 0.0 };
+                t.width = size;
+                t.height = size;
+            }
+        }
+    }
+}
+
+type PaintUi<'s> = (
+    UiFinder<'s>,
+    WriteStorage<'s, UiText>,
+    WriteStorage<'s, UiImage>,
+    WriteStorage<'s, UiTransform>,
+);
+
+type PaintSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Read<'s, InputHandler<String, String>>,
+    Read<'s, Time>,
+    Read<'s, Picker>,
+    Write<'s, Paint>,
+    Read<'s, Palettes>,
+    WriteStorage<'s, Block>,
+    ReadStorage<'s, MaterialKind>,
+    WriteStorage<'s, Grabbable>,
+    WriteStorage<'s, Material>,
+    ReadStorage<'s, Selected>,
+    ReadStorage<'s, Held>,
+    WriteExpect<'s, BlockAssets>,
+    ReadExpect<'s, Loader>,
+    Read<'s, AssetStorage<Texture>>,
+    ReadExpect<'s, MaterialDefaults>,
+    PaintUi<'s>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for PaintSystem {
+    type SystemData = PaintSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            events,
+            input,
+            time,
+            picker,
+            mut paint,
+            palettes,
+            mut blocks,
+            kinds,
+            mut grabbables,
+            mut materials,
+            selected,
+            held,
+            mut block_assets,
+            loader,
+            tex_storage,
+            defaults,
+            mut ui,
+            resumed,
+        ): Self::SystemData,
+    ) {
+        let axis = |pos: &str, neg: &str| {
+            let is_down = |action: &str| input.action_is_down(action).unwrap_or(false);
+            match (is_down(pos), is_down(neg)) {
+                (true, false) => time.delta_seconds(),
+                (false, true) => -time.delta_seconds(),
+                _ => 0.0,
+            }
+        };
+        let hue = axis("hue_up", "hue_down") * HUE_SPEED;
+        let saturation = axis("saturation_up", "saturation_down") * CHANNEL_SPEED;
+        let value = axis("value_up", "value_down") * CHANNEL_SPEED;
+        paint.adjust(hue, saturation, value);
+
+        let pointed = picker
+            .entity_within(MAX_TOI_PAINT)
+            .map(|(e, _)| e)
+            .filter(|e| blocks.contains(*e));
+        for event in events
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`PaintSystem::setup` was not called before `PaintSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            let action = match *event {
+                InputEvent::ActionPressed(ref action) => action.as_str(),
+                _ => continue,
+            };
+            match action {
+                "paint" => {
+                    let pointed = match pointed {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                    let color = paint.color();
+                    for entity in Self::targets(pointed, &entities, &selected) {
+                        if let (Some(block), Some(kind)) =
+                            (blocks.get_mut(entity), kinds.get(entity))
+                        {
+                            block.color = color;
+                            block.texture = None;
+                            let g = block_assets.grabbable(
+                                Surface::color(color),
+                                *kind,
+                                &loader,
+                                &tex_storage,
+                                &defaults.0,
+                            );
+                            let highlighted = selected.contains(entity) || held.contains(entity);
+                            materials.insert(entity, g.material(highlighted)).unwrap();
+                            grabbables.insert(entity, g).unwrap();
+                        }
+                    }
+                }
+                "next_texture" => {
+                    let pointed = match pointed {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                    // no texture, then each of them in turn
+                    let current = blocks
+                        .get(pointed)
+                        .and_then(|b| b.texture.as_ref())
+                        .and_then(|t| TEXTURES.iter().position(|n| n == t));
+                    let texture = match current {
+                        None => Some(TEXTURES[0]),
+                        Some(i) => TEXTURES.get(i + 1).cloned(),
+                    };
+                    for entity in Self::targets(pointed, &entities, &selected) {
+                        if let Some(block) = blocks.get_mut(entity) {
+                            block.texture = texture.map(str::to_owned);
+                        }
+                    }
+                }
+                "pick_color" => {
+                    if let Some(block) = pointed.and_then(|e| blocks.get(e)) {
+                        paint.set_color(block.color);
+                    }
+                }
+                "next_palette" => paint.next_palette(&palettes),
+                "save_swatch" => paint.save_swatch(),
+                _ if action.starts_with("swatch_") => {
+                    let color = action["swatch_".len()..]
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| paint.swatches(&palettes).1.get(i).cloned());
+                    if let Some(color) = color {
+                        paint.set_color(color);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        self.show(
+            &paint,
+            &palettes,
+            &mut block_assets,
+            &loader,
+            &tex_storage,
+            &mut ui,
+        );
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use crate::{
+    components::{Block, Grabbable, Held, MaterialKind, Selected},
+    patterns::pattern_for,
+    resources::{Accessibility, BlockAssets, Palettes, Resumed, Surface},
+};
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::{
+        Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage,
+    },
+    input::InputEvent,
+    renderer::{Material, MaterialDefaults, Texture},
+    shrev::{EventChannel, ReaderId},
+};
+
+use specs::{prelude::Resources, Entity};
+
+/// Redraws blocks with the pattern for their color while `Accessibility::patterns` is on, and
+/// with their plain color once it is turned off.
+#[derive(Default)]
+pub struct PatternSystem {
+    /// The color each block was last drawn with and whether it had a pattern, blocks that
+    /// aren't here were drawn plain.
+    drawn: HashMap<Entity, ([f32; 4], bool)>,
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+type PatternSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Write<'s, Accessibility>,
+    Read<'s, Palettes>,
+    ReadStorage<'s, Block>,
+    ReadStorage<'s, MaterialKind>,
+    ReadStorage<'s, Selected>,
+    ReadStorage<'s, Held>,
+    WriteStorage<'s, Grabbable>,
+    WriteStorage<'s, Material>,
+    WriteExpect<'s, BlockAssets>,
+    ReadExpect<'s, Loader>,
+    Read<'s, AssetStorage<Texture>>,
+    ReadExpect<'s, MaterialDefaults>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for PatternSystem {
+    type SystemData = PatternSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            events,
+            mut accessibility,
+            palettes,
+            blocks,
+            kinds,
+            selected,
+            held,
+            mut grabbables,
+            mut materials,
+            mut block_assets,
+            loader,
+            tex_storage,
+            defaults,
+            resumed,
+        ): Self::SystemData,
+    ) {
+        for event in events
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`PatternSystem::setup` was not called before `PatternSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            if let InputEvent::ActionPressed(ref action) = *event {
+                if action == "toggle_patterns" {
+                    accessibility.patterns = !accessibility.patterns;
+                }
+            }
+        }
+
+        self.drawn.retain(|e, _| entities.is_alive(*e));
+        for (entity, block, kind) in (&entities, &blocks, &kinds).join() {
+            // `TextureSystem` draws textured blocks
+            if block.texture.is_some() {
+                self.drawn.remove(&entity);
+                continue;
+            }
+            let wanted = (block.color, accessibility.patterns);
+            let drawn = self
+                .drawn
+                .get(&entity)
+                .cloned()
+                .unwrap_or((block.color, false));
+            if drawn == wanted || !grabbables.contains(entity) {
+                continue;
+            }
+            let surface = if accessibility.patterns {
+                Surface::pattern(block.color, pattern_for(block.color, &palettes))
+            } else {
+                Surface::color(block.color)
+            };
+            let g = block_assets.grabbable(surface, *kind, &loader, &tex_storage, &defaults.0);
+            let highlighted = selected.contains(entity) || held.contains(entity);
+            materials.insert(entity, g.material(highlighted)).unwrap();
+            grabbables.insert(entity, g).unwrap();
+            self.drawn.insert(entity, wanted);
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
@@ -1,15 +1,180 @@
-use crate::{components::PhysicsBody, resources::MyWorld};
+use std::{collections::HashMap, mem, time::Instant};
+
+use crate::{
+    components::PhysicsBody,
+    resources::{CollisionEvent, MyWorld, PhysicsClock, PhysicsHistory, PhysicsStats},
+};
 
 use amethyst::{
     core::{timing::Time, Transform},
     ecs::{Join, Read, ReadStorage, System, Write, WriteStorage},
+    shrev::EventChannel,
 };
 
+use na::Isometry3;
+use ncollide3d::{events::ContactEvent, world::CollisionObjectHandle};
+use nphysics3d::{
+    math::Velocity,
+    object::{BodyHandle, BodyStatus},
+};
+use specs::Entity;
+
 const MAX_STEPS_PER_RUN: u8 = 4;
 
+fn velocities(
+    physics_world: &MyWorld,
+    handles: &[BodyHandle],
+) -> HashMap<BodyHandle, Velocity<f32>> {
+    handles
+        .iter()
+        .filter_map(|bh| {
+            physics_world
+                .rigid_body(*bh)
+                .map(|rb| (*bh, *rb.velocity()))
+        })
+        .collect()
+}
+
+fn write_pose(t: &mut Transform, previous: &Isometry3<f32>, pos: &Isometry3<f32>, alpha: f32) {
+    *t.translation_mut() =
+        previous.translation.vector * (1.0 - alpha) + pos.translation.vector * alpha;
+    *t.rotation_mut() = previous
+        .rotation
+        .try_slerp(&pos.rotation, alpha, 1.0e-6)
+        .unwrap_or(pos.rotation);
+}
+
 #[derive(Default)]
 pub struct PhysicsSystem {
     time_accumulator: f32,
+    /// Bodies that were awake after the last step, the only ones whose transform can change.
+    active_bodies: Vec<(Entity, BodyHandle)>,
+    /// Poses before the last step, used to interpolate towards the current ones.
+    previous_poses: HashMap<Entity, Isometry3<f32>>,
+}
+
+impl PhysicsSystem {
+    fn steps_to_run(
+        &mut self,
+        clock: &mut PhysicsClock,
+        stats: &mut PhysicsStats,
+        timestep: f32,
+        delta: f32,
+    ) -> u8 {
+        if clock.paused {
+            self.time_accumulator = 0.0;
+            if clock.pending_steps > 0 {
+                clock.pending_steps -= 1;
+                return 1;
+            }
+            return 0;
+        }
+        self.time_accumulator += delta * clock.time_scale;
+        let mut steps = 0;
+        while steps < MAX_STEPS_PER_RUN && self.time_accumulator >= timestep {
+            self.time_accumulator -= timestep;
+            steps += 1;
+        }
+        if self.time_accumulator >= timestep {
+            let dropped = (self.time_accumulator / timestep).floor();
+            self.time_accumulator -= dropped * timestep;
+            // counted in `PhysicsStats`, this happens every frame while the simulation lags
+            stats.dropped_steps += dropped as u64;
+            debug!(
+                "physics is falling behind, dropped {} steps this frame",
+                dropped
+            );
+        }
+        steps
+    }
+
+    fn store_previous_poses(&mut self, physics_world: &MyWorld) {
+        self.previous_poses.clear();
+        for (entity, bh) in self.active_bodies.iter() {
+            if let Some(rb) = physics_world.rigid_body(*bh) {
+                self.previous_poses.insert(*entity, *rb.position());
+            }
+        }
+    }
+
+    /// Momentum a body gained or lost during the last step, not counting gravity.
+    fn momentum_change(
+        physics_world: &MyWorld,
+        collider: CollisionObjectHandle,
+        velocities_before: &HashMap<BodyHandle, Velocity<f32>>,
+    ) -> f32 {
+        let bh = match physics_world.collider_body_handle(collider) {
+            Some(x) => x,
+            None => return 0.0,
+        };
+        match physics_world.rigid_body(bh) {
+            Some(rb) if rb.status() == BodyStatus::Dynamic => {
+                let before = velocities_before
+                    .get(&bh)
+                    .map(|v| v.linear)
+                    .unwrap_or_else(na::zero);
+                let delta = rb.velocity().linear
+                    - before
+                    - physics_world.gravity() * physics_world.timestep();
+                delta.norm() * rb.local_inertia().linear
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn publish_collision_events(
+        physics_world: &MyWorld,
+        velocities_before: &HashMap<BodyHandle, Velocity<f32>>,
+        collision_events: &mut EventChannel<CollisionEvent>,
+    ) {
+        for event in physics_world.contact_events().iter() {
+            let (h1, h2, started) = match *event {
+                ContactEvent::Started(h1, h2) => (h1, h2, true),
+                ContactEvent::Stopped(h1, h2) => (h1, h2, false),
+            };
+            let (a, b) = match (
+                physics_world.collider_entity(h1),
+                physics_world.collider_entity(h2),
+            ) {
+                (Some(a), Some(b)) => (a, b),
+                _ => continue,
+            };
+            if started {
+                let impulse = Self::momentum_change(physics_world, h1, velocities_before)
+                    .max(Self::momentum_change(physics_world, h2, velocities_before));
+                collision_events.single_write(CollisionEvent::CollisionStarted(a, b));
+                collision_events.single_write(CollisionEvent::Impact { a, b, impulse });
+            } else {
+                collision_events.single_write(CollisionEvent::CollisionEnded(a, b));
+            }
+        }
+    }
+
+    /// Refreshes `active_bodies` after stepping and returns the bodies that just fell asleep,
+    /// which still need their final pose written once.
+    ///
+    /// Only the awake bodies are visited, their entities come from the map kept by `MyWorld`.
+    fn update_active_bodies(&mut self, physics_world: &MyWorld) -> Vec<(Entity, BodyHandle)> {
+        let was_active = mem::replace(&mut self.active_bodies, Vec::new());
+        self.active_bodies.extend(
+            physics_world
+                .bodies()
+                .filter(|body| body.is_active())
+                .filter_map(|body| {
+                    let bh = body.handle();
+                    physics_world.body_entity(bh).map(|entity| (entity, bh))
+                }),
+        );
+        was_active
+            .into_iter()
+            .filter(|(_, bh)| {
+                physics_world
+                    .rigid_body(*bh)
+                    .map(|rb| !rb.is_active())
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
 }
 
 impl<'s> System<'s> for PhysicsSystem {
@@ -18,26 +183,83 @@ impl<'s> System<'s> for PhysicsSystem {
         WriteStorage<'s, Transform>,
         ReadStorage<'s, PhysicsBody>,
         Read<'s, Time>,
+        Write<'s, PhysicsClock>,
+        Write<'s, PhysicsHistory>,
+        Write<'s, PhysicsStats>,
+        Write<'s, EventChannel<CollisionEvent>>,
     );
-    fn run(&mut self, (mut physics_world, mut transforms, bodies, time): Self::SystemData) {
-        self.time_accumulator += time.delta_seconds();
+    fn run(
+        &mut self,
+        (
+            mut physics_world,
+            mut transforms,
+            bodies,
+            time,
+            mut clock,
+            mut history,
+            mut stats,
+            mut collision_events,
+        ): Self::SystemData,
+    ) {
         let timestep = physics_world.timestep();
-        for _ in 0..MAX_STEPS_PER_RUN {
-            if self.time_accumulator < timestep {
-                break;
+        let steps = self.steps_to_run(&mut clock, &mut stats, timestep, time.delta_seconds());
+        if steps > 0 && !history.rewinding {
+            let handles = bodies
+                .join()
+                .flat_map(|body| physics_world.collider_body_handle(body.0))
+                .collect::<Vec<_>>();
+            for i in 0..steps {
+                history.record(&physics_world, &handles);
+                if i == steps - 1 {
+                    self.store_previous_poses(&physics_world);
+                }
+                let velocities_before = velocities(&physics_world, &handles);
+                physics_world.step();
+                stats.steps += 1;
+                Self::publish_collision_events(
+                    &physics_world,
+                    &velocities_before,
+                    &mut collision_events,
+                );
+            }
+        } else if steps > 0 {
+            // rewound poses are shown as they are, without interpolating
+            self.previous_poses.clear();
+            for _ in 0..steps {
+                if !history.rewind(&mut physics_world) {
+                    break;
+                }
+            }
+        }
+
+        let sync_start = Instant::now();
+        let settled = if steps > 0 {
+            self.update_active_bodies(&physics_world)
+        } else {
+            Vec::new()
+        };
+        let alpha = if clock.paused || history.rewinding {
+            1.0
+        } else {
+            self.time_accumulator / timestep
+        };
+        for (entity, bh) in self.active_bodies.iter() {
+            if let (Some(t), Some(rb)) =
+                (transforms.get_mut(*entity), physics_world.rigid_body(*bh))
+            {
+                let pos = rb.position();
+                let previous = self.previous_poses.get(entity).unwrap_or(pos);
+                write_pose(t, previous, pos, alpha);
             }
-            physics_world.step();
-            self.time_accumulator -= timestep;
         }
-        for (mut t, body) in (&mut transforms, &bodies).join() {
-            if let Some(pos) = physics_world
-                .collider_body_handle(body.0)
-                .and_then(|bh| physics_world.rigid_body(bh))
-                .map(|co| co.position())
+        for (entity, bh) in settled.iter() {
+            if let (Some(t), Some(rb)) =
+                (transforms.get_mut(*entity), physics_world.rigid_body(*bh))
             {
-                *t.translation_mut() = pos.translation.vector;
-                *t.rotation_mut() = pos.rotation;
+                write_pose(t, rb.position(), rb.position(), 1.0);
             }
         }
+        stats.synced_bodies = self.active_bodies.len() + settled.len();
+        stats.sync_time = sync_start.elapsed();
     }
 }
@@ -0,0 +1,55 @@
+use crate::resources::{PhysicsClock, PhysicsHistory, Resumed};
+
+use amethyst::{
+    ecs::{Read, System, Write},
+    input::{InputEvent, InputHandler},
+    shrev::{EventChannel, ReaderId},
+};
+use specs::prelude::Resources;
+
+const TIME_SCALE_FACTOR: f32 = 2.0;
+
+#[derive(Default)]
+pub struct PhysicsControlSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+impl<'s> System<'s> for PhysicsControlSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<String>>>,
+        Read<'s, InputHandler<String, String>>,
+        Write<'s, PhysicsClock>,
+        Write<'s, PhysicsHistory>,
+        Read<'s, Resumed>,
+    );
+
+    fn run(&mut self, (events, input, mut clock, mut history, resumed): Self::SystemData) {
+        for event in events
+            .read(self.event_reader.as_mut().expect(
+                "`PhysicsControlSystem::setup` was not called before `PhysicsControlSystem::run`",
+            ))
+            .filter(|_| !resumed.0)
+        {
+            if let InputEvent::ActionPressed(ref action) = *event {
+                match action.as_str() {
+                    "toggle_physics_pause" => clock.toggle_pause(),
+                    "physics_slower" => clock.scale_by(1.0 / TIME_SCALE_FACTOR),
+                    "physics_faster" => clock.scale_by(TIME_SCALE_FACTOR),
+                    "physics_step" => clock.request_step(),
+                    _ => (),
+                }
+            }
+        }
+        history.rewinding = input.action_is_down("rewind_physics").unwrap_or(false);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use std::cmp::Ordering;
+
+use super::camera::player_eye;
+use crate::{
+    components::{CameraSelf, PhysicsBody},
+    resources::{CameraMode, MyWorld, PickHit, Picker, BLOCK_GROUP, STATIC_GROUP},
+};
+
+use amethyst::{
+    core::{nalgebra::Vector3, Transform},
+    ecs::{Join, Read, ReadStorage, System, Write},
+    renderer::Camera,
+};
+
+use na::{Point3, Vector3 as PhysicsVector3};
+use ncollide3d::{query::Ray, world::CollisionGroups};
+
+/// Casts the crosshair ray against the collision world's broad phase and stores the closest
+/// hit in the `Picker` resource.
+///
+/// The ray starts at the camera, but in third person the reach of the hit is measured from the
+/// player's eyes so tools reach as far as they do in first person.
+#[derive(Default)]
+pub struct PickSystem;
+
+type PickSystemData<'s> = (
+    ReadStorage<'s, Camera>,
+    ReadStorage<'s, Transform>,
+    Read<'s, MyWorld>,
+    Read<'s, CameraMode>,
+    ReadStorage<'s, CameraSelf>,
+    ReadStorage<'s, PhysicsBody>,
+    Write<'s, Picker>,
+);
+
+impl<'s> System<'s> for PickSystem {
+    type SystemData = PickSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (cameras, transforms, physics_world, mode, cameraself, physics_bodies, mut picker): Self::SystemData,
+    ) {
+        let isometry = match (&cameras, &transforms).join().next() {
+            Some((_, t)) => t.isometry(),
+            None => return,
+        };
+        let r = isometry.rotation * Vector3::z();
+        let ray = Ray::new(
+            Point3::new(
+                isometry.translation.vector.x,
+                isometry.translation.vector.y,
+                isometry.translation.vector.z,
+            ),
+            PhysicsVector3::new(-r.x, -r.y, -r.z),
+        );
+        let groups = CollisionGroups::new().with_whitelist(&[BLOCK_GROUP, STATIC_GROUP]);
+        let eye = match *mode {
+            CameraMode::ThirdPerson => (&physics_bodies, &cameraself)
+                .join()
+                .filter_map(|(body, _)| physics_world.collider(body.0))
+                .map(player_eye)
+                .next(),
+            _ => None,
+        }
+        .unwrap_or(isometry.translation.vector);
+
+        picker.hit = physics_world
+            .collision_world()
+            .interferences_with_ray(&ray, &groups)
+            .min_by(|(_, i1), (_, i2)| i1.toi.partial_cmp(&i2.toi).unwrap_or(Ordering::Equal))
+            .map(|(co, intersection)| {
+                let point = ray.origin + ray.dir * intersection.toi;
+                PickHit {
+                    entity: physics_world.collider_entity(co.handle()),
+                    toi: intersection.toi,
+                    reach: (point.coords - eye).norm(),
+                    point,
+                    normal: intersection.normal,
+                }
+            });
+    }
+}
@@ -0,0 +1,33 @@
+use crate::resources::Settings;
+
+use amethyst::{
+    ecs::{Join, Read, ReadExpect, System, WriteStorage},
+    renderer::{Camera, Projection, ScreenDimensions},
+};
+
+/// Keeps the camera's projection matching the window's aspect ratio as it is resized, and the
+/// field of view of the `Settings`.
+#[derive(Default)]
+pub struct ProjectionSystem {
+    /// The width, height and field of view the projection was last built for.
+    applied: Option<(f32, f32, f32)>,
+}
+
+impl<'s> System<'s> for ProjectionSystem {
+    type SystemData = (
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, Settings>,
+        WriteStorage<'s, Camera>,
+    );
+
+    fn run(&mut self, (screen, settings, mut cameras): Self::SystemData) {
+        let wanted = (screen.width(), screen.height(), settings.fov_radians());
+        if self.applied == Some(wanted) || screen.height() <= 0.0 {
+            return;
+        }
+        for camera in (&mut cameras).join() {
+            *camera = Camera::from(Projection::perspective(screen.aspect_ratio(), wanted.2));
+            self.applied = Some(wanted);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::PI,
+};
+
+use crate::{
+    components::{Grabbable, Held, Selected},
+    resources::{Accessibility, Picker, Resumed},
+};
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    core::timing::Time,
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
+    input::InputEvent,
+    renderer::{Material, Texture, TextureHandle},
+    shrev::{EventChannel, ReaderId},
+};
+
+use specs::{prelude::Resources, Entity};
+
+const PULSE_LEVELS: usize = 8;
+/// Pulses per second.
+const PULSE_FREQUENCY: f32 = 1.5;
+/// Emission at the top of a selected block's pulse, the pointed one pulses at half of it.
+const MAX_EMISSION: f32 = 0.35;
+const HOVER_AMPLITUDE: f32 = 0.5;
+const MAX_TOI_HOVER: f32 = 4.0;
+
+/// Pulses the brightness of the pointed block and of the selected and held ones while
+/// `Accessibility::pulse` is on, so they stand out whatever their color.
+#[derive(Default)]
+pub struct PulseSystem {
+    emissions: Vec<TextureHandle>,
+    /// Pulsing blocks and the level they were last drawn with.
+    pulsing: HashMap<Entity, usize>,
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+impl PulseSystem {
+    fn prepare_emissions(&mut self, loader: &Loader, tex_storage: &AssetStorage<Texture>) {
+        if !self.emissions.is_empty() {
+            return;
+        }
+        self.emissions = (0..PULSE_LEVELS)
+            .map(|i| {
+                let v = i as f32 / (PULSE_LEVELS - 1) as f32 * MAX_EMISSION;
+                loader.load_from_data([v, v, v, 1.0].into(), (), tex_storage)
+            })
+            .collect();
+    }
+}
+
+type PulseSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Write<'s, Accessibility>,
+    Read<'s, Time>,
+    Read<'s, Picker>,
+    ReadStorage<'s, Grabbable>,
+    ReadStorage<'s, Selected>,
+    ReadStorage<'s, Held>,
+    WriteStorage<'s, Material>,
+    ReadExpect<'s, Loader>,
+    Read<'s, AssetStorage<Texture>>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for PulseSystem {
+    type SystemData = PulseSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            events,
+            mut accessibility,
+            time,
+            picker,
+            grabbables,
+            selected,
+            held,
+            mut materials,
+            loader,
+            tex_storage,
+            resumed,
+        ): Self::SystemData,
+    ) {
+        for event in events
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`PulseSystem::setup` was not called before `PulseSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            if let InputEvent::ActionPressed(ref action) = *event {
+                if action == "toggle_pulse" {
+                    accessibility.pulse = !accessibility.pulse;
+                }
+            }
+        }
+        self.prepare_emissions(&loader, &tex_storage);
+
+        let mut amplitudes = HashMap::new();
+        if accessibility.pulse {
+            if let Some((e, _)) = picker.entity_within(MAX_TOI_HOVER) {
+                amplitudes.insert(e, HOVER_AMPLITUDE);
+            }
+            for (e, _, _) in (&entities, &grabbables, &selected).join() {
+                amplitudes.insert(e, 1.0);
+            }
+            for (e, _, _) in (&entities, &grabbables, &held).join() {
+                amplitudes.insert(e, 1.0);
+            }
+        }
+
+        let phase =
+            ((time.absolute_time_seconds() as f32 * PULSE_FREQUENCY * 2.0 * PI).sin() + 1.0) / 2.0;
+        for (entity, amplitude) in amplitudes.iter() {
+            let g = match grabbables.get(*entity) {
+                Some(x) => x,
+                None => continue,
+            };
+            let level = (phase * amplitude * (PULSE_LEVELS - 1) as f32).round() as usize;
+            if self.pulsing.get(entity) == Some(&level) {
+                continue;
+            }
+            let highlighted = selected.contains(*entity) || held.contains(*entity);
+            let material = Material {
+                emission: self.emissions[level].clone(),
+                ..g.material(highlighted)
+            };
+            materials.insert(*entity, material).unwrap();
+            self.pulsing.insert(*entity, level);
+        }
+
+        let stopped: HashSet<Entity> = self
+            .pulsing
+            .keys()
+            .filter(|e| !amplitudes.contains_key(e))
+            .cloned()
+            .collect();
+        for entity in stopped {
+            self.pulsing.remove(&entity);
+            if let (true, Some(g)) = (entities.is_alive(entity), grabbables.get(entity)) {
+                let highlighted = selected.contains(entity) || held.contains(entity);
+                materials.insert(entity, g.material(highlighted)).unwrap();
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
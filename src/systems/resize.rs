@@ -0,0 +1,178 @@
+use std::{cmp::Ordering, f32};
+
+use crate::{
+    blocks::{create_block_body, GRID_STEP},
+    components::{Block, Held, Locked, PhysicsBody},
+    resources::{MyWorld, PickHit, Picker, Resumed},
+};
+
+use amethyst::{
+    core::Transform,
+    ecs::{Read, ReadStorage, System, Write, WriteStorage},
+    input::InputEvent,
+    shrev::{EventChannel, ReaderId},
+};
+
+use na::Vector3;
+use nphysics3d::object::BodyStatus;
+use specs::prelude::Resources;
+
+const MAX_TOI_RESIZE: f32 = 4.0;
+const MIN_SIZE: f32 = GRID_STEP;
+const MAX_SIZE: f32 = 4.0;
+
+/// Grows or shrinks the pointed block by one grid step along the axis of the face it is
+/// pointed at, keeping the opposite face in place.
+///
+/// The body is replaced by one built for the new extents, so its mass and inertia follow, and
+/// the transform scale is updated in the same frame. Held blocks aren't resized, `MoveSystem`
+/// keeps forces on their current body.
+#[derive(Default)]
+pub struct ResizeSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+impl ResizeSystem {
+    fn resize(
+        hit: PickHit,
+        steps: i32,
+        physics_world: &mut MyWorld,
+        body: &mut PhysicsBody,
+        block: &mut Block,
+        transform: &mut Transform,
+        is_locked: bool,
+    ) {
+        let bh = match physics_world.collider_body_handle(body.0) {
+            Some(x) => x,
+            None => return,
+        };
+        let (mut pos, velocity) = match physics_world.rigid_body(bh) {
+            Some(rb) => (*rb.position(), *rb.velocity()),
+            None => return,
+        };
+
+        // the block's axis closest to the face normal
+        let normal = pos.rotation.inverse() * hit.normal;
+        let axis = (0..3)
+            .max_by(|a, b| {
+                normal[*a]
+                    .abs()
+                    .partial_cmp(&normal[*b].abs())
+                    .unwrap_or(Ordering::Equal)
+            })
+            .unwrap();
+        let size = (block.half_extents[axis] * 2.0 / GRID_STEP).round() * GRID_STEP;
+        let new_size = (size + steps as f32 * GRID_STEP)
+            .max(MIN_SIZE)
+            .min(MAX_SIZE);
+        let delta = new_size / 2.0 - block.half_extents[axis];
+        if delta.abs() < f32::EPSILON {
+            return;
+        }
+
+        let mut shift = Vector3::zeros();
+        shift[axis] = delta * normal[axis].signum();
+        pos.translation.vector += pos.rotation * shift;
+        block.half_extents[axis] = new_size / 2.0;
+
+        physics_world.remove_bodies(&[bh]);
+        let collider = create_block_body(physics_world, &block.half_extents, pos, velocity);
+        if is_locked {
+            let bh = physics_world.collider_body_handle(collider).unwrap();
+            physics_world
+                .rigid_body_mut(bh)
+                .unwrap()
+                .set_status(BodyStatus::Static);
+        }
+        body.0 = collider;
+
+        *transform.scale_mut() = block.half_extents;
+        *transform.translation_mut() = pos.translation.vector;
+    }
+}
+
+type ResizeSystemData<'s> = (
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Read<'s, Picker>,
+    Write<'s, MyWorld>,
+    WriteStorage<'s, PhysicsBody>,
+    WriteStorage<'s, Block>,
+    WriteStorage<'s, Transform>,
+    ReadStorage<'s, Held>,
+    ReadStorage<'s, Locked>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for ResizeSystem {
+    type SystemData = ResizeSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            events,
+            picker,
+            mut physics_world,
+            mut physics_bodies,
+            mut blocks,
+            mut transforms,
+            held,
+            locked,
+            resumed,
+        ): Self::SystemData,
+    ) {
+        let mut steps = 0;
+        for event in events
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`ResizeSystem::setup` was not called before `ResizeSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            if let InputEvent::ActionPressed(ref action) = *event {
+                match action.as_str() {
+                    "grow_block" => steps += 1,
+                    "shrink_block" => steps -= 1,
+                    _ => (),
+                }
+            }
+        }
+        if steps == 0 {
+            return;
+        }
+        let (entity, hit) = match picker
+            .entity_within(MAX_TOI_RESIZE)
+            .filter(|(e, _)| !held.contains(*e))
+        {
+            Some(x) => x,
+            None => return,
+        };
+        if let (Some(body), Some(block), Some(transform)) = (
+            physics_bodies.get_mut(entity),
+            blocks.get_mut(entity),
+            transforms.get_mut(entity),
+        ) {
+            Self::resize(
+                hit,
+                steps,
+                &mut physics_world,
+                body,
+                block,
+                transform,
+                locked.contains(entity),
+            );
+            // the body may have been rebuilt with a new collider
+            physics_world.attach(body.0, entity);
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
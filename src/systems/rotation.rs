@@ -1,31 +1,22 @@
 use std::f32;
 
+use crate::resources::{Resumed, Settings};
+
 use amethyst::{
     controls::{HideCursor, WindowFocus},
     core::{nalgebra::Vector3, Transform},
     ecs::{Join, Read, ReadStorage, System, WriteStorage},
     input::InputHandler,
-    renderer::{Camera, MouseButton},
+    renderer::Camera,
     shrev::{EventChannel, ReaderId},
 };
 use specs::prelude::Resources;
 use winit::{DeviceEvent, Event};
+#[derive(Default)]
 pub struct RotationSystem {
-    sensitivity_x: f32,
-    sensitivity_y: f32,
     event_reader: Option<ReaderId<Event>>,
 }
 
-impl Default for RotationSystem {
-    fn default() -> Self {
-        RotationSystem {
-            sensitivity_x: 0.2,
-            sensitivity_y: 0.2,
-            event_reader: None,
-        }
-    }
-}
-
 type RotationSystemData<'s> = (
     Read<'s, EventChannel<Event>>,
     WriteStorage<'s, Transform>,
@@ -33,24 +24,37 @@ type RotationSystemData<'s> = (
     Read<'s, WindowFocus>,
     Read<'s, HideCursor>,
     Read<'s, InputHandler<String, String>>,
+    Read<'s, Settings>,
+    Read<'s, Resumed>,
 );
 
 impl<'s> System<'s> for RotationSystem {
     type SystemData = RotationSystemData<'s>;
 
-    fn run(&mut self, (events, mut transforms, cameras, focus, hide, input): Self::SystemData) {
-        for event in events.read(
-            &mut self
-                .event_reader
-                .as_mut()
-                .expect("`RotationSystem::setup` was not called before `RotationSystem::run`"),
-        ) {
-            if !input.mouse_button_is_down(MouseButton::Right) && focus.is_focused && hide.hide {
+    fn run(
+        &mut self,
+        (events, mut transforms, cameras, focus, hide, input, settings, resumed): Self::SystemData,
+    ) {
+        for event in events
+            .read(
+                &mut self
+                    .event_reader
+                    .as_mut()
+                    .expect("`RotationSystem::setup` was not called before `RotationSystem::run`"),
+            )
+            .filter(|_| !resumed.0)
+        {
+            if !input.action_is_down("rotate_block").unwrap_or(false)
+                && focus.is_focused
+                && hide.hide
+            {
                 if let Event::DeviceEvent { ref event, .. } = *event {
                     if let DeviceEvent::MouseMotion { delta: (x, y) } = *event {
+                        let y = if settings.invert_y { -y } else { y };
                         for (transform, _) in (&mut transforms, &cameras).join() {
-                            transform.pitch_local((-y as f32 * self.sensitivity_y).to_radians());
-                            transform.yaw_global((-x as f32 * self.sensitivity_x).to_radians());
+                            transform
+                                .pitch_local((-y as f32 * settings.sensitivity_y).to_radians());
+                            transform.yaw_global((-x as f32 * settings.sensitivity_x).to_radians());
                             // there's probably a better way to do this if you know trigonometry :see_no_evil:
                             while (transform.isometry().rotation * Vector3::z()).y < -0.8 {
                                 transform.pitch_local((-1.0_f32).to_radians());
@@ -0,0 +1,183 @@
+use crate::{
+    components::{Grabbable, Held, Selected},
+    resources::Picker,
+};
+
+use amethyst::{
+    controls::HideCursor,
+    core::{
+        nalgebra::{Matrix4, Vector2, Vector3, Vector4},
+        Transform,
+    },
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    input::InputHandler,
+    renderer::{Camera, Material, ScreenDimensions},
+    ui::{UiFinder, UiTransform},
+};
+
+/// Below this angle, in radians, between the crosshair at the start and the end of a drag, it
+/// counts as a click.
+const CLICK_ANGLE: f32 = 0.02;
+
+/// Projects a world point to normalized device coordinates, `None` when it is behind the camera.
+fn project(view_proj: &Matrix4<f32>, point: &Vector3<f32>) -> Option<Vector2<f32>> {
+    let clip = view_proj * Vector4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= 0.0 {
+        None
+    } else {
+        Some(Vector2::new(clip.x / clip.w, clip.y / clip.w))
+    }
+}
+
+/// Edits the selection with `grab` while `select_modifier` is held: clicking toggles the
+/// pointed block and clicking nothing clears the selection, dragging sweeps a box from where the
+/// crosshair was when the drag started and adds every block projected inside it.
+#[derive(Default)]
+pub struct SelectionSystem {
+    /// The crosshair direction when the drag started.
+    drag_start: Option<Vector3<f32>>,
+    /// The cursor was released since `grab` was last up, so the click capturing it back isn't
+    /// taken as a selection.
+    awaiting_release: bool,
+}
+
+impl SelectionSystem {
+    /// The box between the drag start and the crosshair, as its two corners in normalized
+    /// device coordinates.
+    fn drag_box(
+        &self,
+        view_proj: &Matrix4<f32>,
+        camera_position: &Vector3<f32>,
+    ) -> Option<(Vector2<f32>, Vector2<f32>)> {
+        let start = project(view_proj, &(camera_position + self.drag_start?))?;
+        let center = Vector2::new(0.0, 0.0);
+        Some((
+            Vector2::new(start.x.min(center.x), start.y.min(center.y)),
+            Vector2::new(start.x.max(center.x), start.y.max(center.y)),
+        ))
+    }
+}
+
+type SelectionSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, Picker>,
+    Read<'s, InputHandler<String, String>>,
+    ReadStorage<'s, Camera>,
+    ReadStorage<'s, Transform>,
+    ReadStorage<'s, Grabbable>,
+    ReadStorage<'s, Held>,
+    WriteStorage<'s, Selected>,
+    WriteStorage<'s, Material>,
+    UiFinder<'s>,
+    WriteStorage<'s, UiTransform>,
+    ReadExpect<'s, ScreenDimensions>,
+    Read<'s, HideCursor>,
+);
+
+impl<'s> System<'s> for SelectionSystem {
+    type SystemData = SelectionSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            picker,
+            input,
+            cameras,
+            transforms,
+            grabbables,
+            held,
+            mut selected,
+            mut materials,
+            finder,
+            mut ui_transforms,
+            screen,
+            hide,
+        ): Self::SystemData,
+    ) {
+        let (camera, camera_transform) = match (&cameras, &transforms).join().next() {
+            Some(x) => x,
+            None => return,
+        };
+        let camera_position = camera_transform.isometry().translation.vector;
+        let forward = -(camera_transform.isometry().rotation * Vector3::z());
+        let view_proj = camera.proj
+            * camera_transform
+                .matrix()
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity);
+
+        if !hide.hide {
+            self.drag_start = None;
+            self.awaiting_release = true;
+        } else if !input.action_is_down("grab").unwrap_or(false) {
+            self.awaiting_release = false;
+        }
+        let is_down = input.action_is_down("grab").unwrap_or(false)
+            && input.action_is_down("select_modifier").unwrap_or(false)
+            && !self.awaiting_release;
+        let drag_box = self.drag_box(&view_proj, &camera_position);
+
+        if let Some(t) = finder
+            .find("selection_box")
+            .and_then(|e| ui_transforms.get_mut(e))
+        {
+            let (min, max) = drag_box
+                .filter(|_| is_down)
+                .unwrap_or((Vector2::new(0.0, 0.0), Vector2::new(0.0, 0.0)));
+            let half_width = screen.width() / 2.0;
+            let half_height = screen.height() / 2.0;
+            t.local_x = (min.x + max.x) / 2.0 * half_width;
+            t.local_y = (min.y + max.y) / 2.0 * half_height;
+            t.width = (max.x - min.x) * half_width;
+            t.height = (max.y - min.y) * half_height;
+        }
+
+        let start = match (is_down, self.drag_start) {
+            (true, None) => {
+                self.drag_start = Some(forward);
+                return;
+            }
+            (false, Some(start)) => start,
+            _ => return,
+        };
+        self.drag_start = None;
+
+        let mut toggled = vec![];
+        if start.angle(&forward) < CLICK_ANGLE {
+            match picker
+                .hit
+                .and_then(|hit| hit.entity)
+                .filter(|e| grabbables.contains(*e))
+            {
+                Some(entity) => toggled.push(entity),
+                None => toggled.extend((&entities, &selected).join().map(|(e, _)| e)),
+            }
+        } else if let Some((min, max)) = drag_box {
+            toggled.extend(
+                (&entities, &transforms, &grabbables, !&selected)
+                    .join()
+                    .filter(|(_, t, _, _)| {
+                        project(&view_proj, &t.translation()).map_or(false, |p| {
+                            p.x >= min.x && p.x <= max.x && p.y >= min.y && p.y <= max.y
+                        })
+                    })
+                    .map(|(e, _, _, _)| e),
+            );
+        }
+
+        for entity in toggled {
+            let is_selected = if selected.contains(entity) {
+                selected.remove(entity);
+                false
+            } else {
+                selected.insert(entity, Selected).unwrap();
+                true
+            };
+            let g = grabbables.get(entity).unwrap();
+            materials
+                .insert(entity, g.material(is_selected || held.contains(entity)))
+                .unwrap();
+        }
+    }
+}
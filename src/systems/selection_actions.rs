@@ -0,0 +1,176 @@
+use crate::{
+    components::{Block, Grabbable, Locked, MaterialKind, PhysicsBody, Selected},
+    resources::{BlockAssets, MyWorld, Paint, Palettes, Resumed, Surface},
+};
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::{
+        Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage,
+    },
+    input::InputEvent,
+    renderer::{Material, MaterialDefaults, Texture},
+    shrev::{EventChannel, ReaderId},
+};
+
+use nphysics3d::{math::Velocity, object::BodyStatus};
+use specs::prelude::Resources;
+
+/// Applies group operations to every selected block: delete, recolor and lock.
+#[derive(Default)]
+pub struct SelectionActionsSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+impl SelectionActionsSystem {
+    fn delete(
+        entities: &Entities,
+        physics_world: &mut MyWorld,
+        physics_bodies: &ReadStorage<PhysicsBody>,
+        selected: &ReadStorage<Selected>,
+    ) {
+        let mut bodies = vec![];
+        for (entity, body, _) in (entities, physics_bodies, selected).join() {
+            bodies.extend(physics_world.collider_body_handle(body.0));
+            entities.delete(entity).unwrap();
+        }
+        physics_world.remove_bodies(&bodies);
+    }
+
+    /// Locks every selected block, or unlocks them all if they were already locked.
+    fn toggle_lock(
+        entities: &Entities,
+        physics_world: &mut MyWorld,
+        physics_bodies: &ReadStorage<PhysicsBody>,
+        selected: &ReadStorage<Selected>,
+        locked: &mut WriteStorage<Locked>,
+    ) {
+        let lock = (physics_bodies, selected, !&*locked)
+            .join()
+            .next()
+            .is_some();
+        for (entity, body, _) in (entities, physics_bodies, selected).join() {
+            let rb = match physics_world
+                .collider_body_handle(body.0)
+                .and_then(|bh| physics_world.rigid_body_mut(bh))
+            {
+                Some(x) => x,
+                None => continue,
+            };
+            if lock {
+                rb.set_velocity(Velocity::zero());
+                rb.set_status(BodyStatus::Static);
+                locked.insert(entity, Locked).unwrap();
+            } else {
+                rb.set_status(BodyStatus::Dynamic);
+                rb.activate();
+                locked.remove(entity);
+            }
+        }
+    }
+}
+
+type SelectionActionsSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Write<'s, MyWorld>,
+    ReadStorage<'s, PhysicsBody>,
+    ReadStorage<'s, Selected>,
+    WriteStorage<'s, Locked>,
+    WriteStorage<'s, Block>,
+    ReadStorage<'s, MaterialKind>,
+    WriteStorage<'s, Grabbable>,
+    WriteStorage<'s, Material>,
+    WriteExpect<'s, BlockAssets>,
+    ReadExpect<'s, Loader>,
+    Read<'s, AssetStorage<Texture>>,
+    ReadExpect<'s, MaterialDefaults>,
+    Read<'s, Paint>,
+    Read<'s, Palettes>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for SelectionActionsSystem {
+    type SystemData = SelectionActionsSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            events,
+            mut physics_world,
+            physics_bodies,
+            selected,
+            mut locked,
+            mut blocks,
+            kinds,
+            mut grabbables,
+            mut materials,
+            mut block_assets,
+            loader,
+            tex_storage,
+            defaults,
+            paint,
+            palettes,
+            resumed,
+        ): Self::SystemData,
+    ) {
+        for event in events.read(self.event_reader.as_mut().expect(
+            "`SelectionActionsSystem::setup` was not called before `SelectionActionsSystem::run`",
+        )).filter(|_| !resumed.0) {
+            if let InputEvent::ActionPressed(ref action) = *event {
+                match action.as_str() {
+                    "delete_selection" => {
+                        Self::delete(&entities, &mut physics_world, &physics_bodies, &selected)
+                    }
+                    "lock_selection" => Self::toggle_lock(
+                        &entities,
+                        &mut physics_world,
+                        &physics_bodies,
+                        &selected,
+                        &mut locked,
+                    ),
+                    "recolor_selection" => {
+                        // every selected block takes the color after the first one's in the
+                        // paint tool's palette
+                        let (_, colors) = paint.swatches(&palettes);
+                        let current = (&blocks, &selected).join().next().map(|(b, _)| b.color);
+                        let color = match current {
+                            Some(current) if !colors.is_empty() => {
+                                let i = colors.iter().position(|c| *c == current);
+                                colors[i.map_or(0, |i| (i + 1) % colors.len())]
+                            }
+                            _ => continue,
+                        };
+                        for (entity, block, kind, _) in
+                            (&entities, &mut blocks, &kinds, &selected).join()
+                        {
+                            block.color = color;
+                            block.texture = None;
+                            let g = block_assets.grabbable(
+                                Surface::color(color),
+                                *kind,
+                                &loader,
+                                &tex_storage,
+                                &defaults.0,
+                            );
+                            materials.insert(entity, g.material(true)).unwrap();
+                            grabbables.insert(entity, g).unwrap();
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use crate::{
+    components::MaterialKind,
+    resources::{CollisionEvent, Sounds, UiSound},
+};
+
+use amethyst::{
+    assets::AssetStorage,
+    audio::{output::Output, Source, SourceHandle},
+    core::timing::Time,
+    ecs::{Read, ReadStorage, System},
+    shrev::{EventChannel, ReaderId},
+};
+use specs::{prelude::Resources, Entity};
+
+/// Impacts below this impulse are not heard.
+const MIN_AUDIBLE_IMPULSE: f32 = 0.5;
+/// Impulse at which impacts are played at full volume.
+const FULL_VOLUME_IMPULSE: f32 = 10.0;
+/// Minimum seconds between two impact sounds of the same pair of entities.
+const PAIR_COOLDOWN: f64 = 0.15;
+const UI_VOLUME: f32 = 0.5;
+
+/// Plays impact and UI sounds. Without an audio device there's no `Output` resource and
+/// events are just consumed.
+#[derive(Default)]
+pub struct SoundSystem {
+    collision_reader: Option<ReaderId<CollisionEvent>>,
+    ui_reader: Option<ReaderId<UiSound>>,
+    last_impact: HashMap<(Entity, Entity), f64>,
+}
+
+impl SoundSystem {
+    fn play(
+        output: &Option<Read<Output>>,
+        storage: &AssetStorage<Source>,
+        handle: Option<&SourceHandle>,
+        volume: f32,
+    ) {
+        if let Some(ref output) = output {
+            if let Some(sound) = handle.and_then(|h| storage.get(h)) {
+                output.play_once(sound, volume);
+            }
+        }
+    }
+}
+
+type SoundSystemData<'s> = (
+    Read<'s, EventChannel<CollisionEvent>>,
+    Read<'s, EventChannel<UiSound>>,
+    Read<'s, AssetStorage<Source>>,
+    Read<'s, Sounds>,
+    Option<Read<'s, Output>>,
+    ReadStorage<'s, MaterialKind>,
+    Read<'s, Time>,
+);
+
+impl<'s> System<'s> for SoundSystem {
+    type SystemData = SoundSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (collision_events, ui_events, storage, sounds, output, kinds, time): Self::SystemData,
+    ) {
+        let now = time.absolute_time_seconds();
+        for event in collision_events.read(
+            self.collision_reader
+                .as_mut()
+                .expect("`SoundSystem::setup` was not called before `SoundSystem::run`"),
+        ) {
+            if let CollisionEvent::Impact { a, b, impulse } = *event {
+                if impulse < MIN_AUDIBLE_IMPULSE {
+                    continue;
+                }
+                let pair = if a.id() < b.id() { (a, b) } else { (b, a) };
+                if let Some(last) = self.last_impact.get(&pair) {
+                    if now - last < PAIR_COOLDOWN {
+                        continue;
+                    }
+                }
+                self.last_impact.insert(pair, now);
+                let volume = (impulse / FULL_VOLUME_IMPULSE).min(1.0);
+                let mut played = None;
+                for kind in [kinds.get(a), kinds.get(b)].iter().flat_map(|k| *k) {
+                    if played != Some(*kind) {
+                        Self::play(&output, &storage, sounds.impacts.get(kind), volume);
+                        played = Some(*kind);
+                    }
+                }
+            }
+        }
+        for sound in ui_events.read(
+            self.ui_reader
+                .as_mut()
+                .expect("`SoundSystem::setup` was not called before `SoundSystem::run`"),
+        ) {
+            Self::play(&output, &storage, sounds.ui.get(sound), UI_VOLUME);
+        }
+        self.last_impact
+            .retain(|_, last| now - *last < PAIR_COOLDOWN);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.collision_reader = Some(
+            res.fetch_mut::<EventChannel<CollisionEvent>>()
+                .register_reader(),
+        );
+        self.ui_reader = Some(res.fetch_mut::<EventChannel<UiSound>>().register_reader());
+    }
+}
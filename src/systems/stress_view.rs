@@ -0,0 +1,221 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::{
+    components::{Grabbable, Held, PhysicsBody, Selected},
+    resources::{MyWorld, PhysicsStats, Resumed},
+};
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    input::InputEvent,
+    renderer::{Material, MaterialDefaults, Texture},
+    shrev::{EventChannel, ReaderId},
+};
+
+use ncollide3d::{bounding_volume::AABB, world::CollisionObjectHandle};
+use specs::{prelude::Resources, Entity};
+
+/// Number of colors between green (only carrying itself) and red.
+const STRESS_LEVELS: usize = 10;
+/// Load, in multiples of a block's own weight, shown as fully red.
+const MAX_STRESS: f32 = 8.0;
+
+struct LoadedBlock {
+    entity: Entity,
+    weight: f32,
+    aabb: AABB<f32>,
+    supports: Vec<CollisionObjectHandle>,
+    load: f32,
+}
+
+/// Computes how much weight every block supports by propagating loads down the contact
+/// graph, from the highest block to the lowest, splitting each block's load evenly between
+/// the blocks it rests on. Whatever rests on static colliders is carried by the ground.
+fn compute_stress(
+    physics_world: &MyWorld,
+    entities: &Entities,
+    physics_bodies: &ReadStorage<PhysicsBody>,
+    grabbables: &ReadStorage<Grabbable>,
+) -> Vec<(Entity, f32)> {
+    let gravity = physics_world.gravity().norm();
+    let mut blocks: HashMap<CollisionObjectHandle, LoadedBlock> = HashMap::new();
+    for (entity, body, _) in (entities, physics_bodies, grabbables).join() {
+        let rb = match physics_world
+            .collider_body_handle(body.0)
+            .and_then(|bh| physics_world.rigid_body(bh))
+        {
+            Some(x) => x,
+            None => continue,
+        };
+        let co = match physics_world.collider(body.0) {
+            Some(x) => x,
+            None => continue,
+        };
+        let weight = rb.local_inertia().linear * gravity;
+        blocks.insert(
+            body.0,
+            LoadedBlock {
+                entity,
+                weight,
+                aabb: co.shape().aabb(co.position()),
+                supports: vec![],
+                load: weight,
+            },
+        );
+    }
+
+    for (co1, co2, contacts) in physics_world.collision_world().contact_pairs() {
+        if contacts.num_contacts() == 0 {
+            continue;
+        }
+        let (h1, h2) = (co1.handle(), co2.handle());
+        let (upper, lower) = match (blocks.get(&h1), blocks.get(&h2)) {
+            (Some(b1), Some(b2)) if b1.aabb.mins().y >= b2.aabb.center().y => (h1, h2),
+            (Some(b1), Some(b2)) if b2.aabb.mins().y >= b1.aabb.center().y => (h2, h1),
+            _ => continue,
+        };
+        blocks.get_mut(&upper).unwrap().supports.push(lower);
+    }
+
+    let mut order: Vec<CollisionObjectHandle> = blocks.keys().cloned().collect();
+    order.sort_by(|h1, h2| {
+        blocks[h2]
+            .aabb
+            .center()
+            .y
+            .partial_cmp(&blocks[h1].aabb.center().y)
+            .unwrap_or(Ordering::Equal)
+    });
+    for handle in order {
+        let (load, supports) = {
+            let block = &blocks[&handle];
+            (block.load, block.supports.clone())
+        };
+        for support in supports.iter() {
+            blocks.get_mut(support).unwrap().load += load / supports.len() as f32;
+        }
+    }
+
+    blocks
+        .values()
+        .map(|block| (block.entity, block.load / block.weight))
+        .collect()
+}
+
+/// Toggleable view tinting blocks from green to red by the load they carry.
+#[derive(Default)]
+pub struct StressViewSystem {
+    enabled: bool,
+    computed_at_step: Option<u64>,
+    materials: Vec<Material>,
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+impl StressViewSystem {
+    fn prepare_materials(
+        &mut self,
+        loader: &Loader,
+        tex_storage: &AssetStorage<Texture>,
+        defaults: &MaterialDefaults,
+    ) {
+        if !self.materials.is_empty() {
+            return;
+        }
+        self.materials = (0..STRESS_LEVELS)
+            .map(|i| {
+                let t = i as f32 / (STRESS_LEVELS - 1) as f32;
+                let color = [(2.0 * t).min(1.0), (2.0 * (1.0 - t)).min(1.0), 0.0, 1.0];
+                Material {
+                    albedo: loader.load_from_data(color.into(), (), tex_storage),
+                    ..defaults.0.clone()
+                }
+            })
+            .collect();
+    }
+}
+
+type StressViewSystemData<'s> = (
+    Entities<'s>,
+    Read<'s, EventChannel<InputEvent<String>>>,
+    Read<'s, MyWorld>,
+    Read<'s, PhysicsStats>,
+    ReadStorage<'s, PhysicsBody>,
+    ReadStorage<'s, Grabbable>,
+    ReadStorage<'s, Selected>,
+    ReadStorage<'s, Held>,
+    WriteStorage<'s, Material>,
+    ReadExpect<'s, Loader>,
+    Read<'s, AssetStorage<Texture>>,
+    ReadExpect<'s, MaterialDefaults>,
+    Read<'s, Resumed>,
+);
+
+impl<'s> System<'s> for StressViewSystem {
+    type SystemData = StressViewSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            events,
+            physics_world,
+            stats,
+            physics_bodies,
+            grabbables,
+            selected,
+            held,
+            mut materials,
+            loader,
+            tex_storage,
+            defaults,
+            resumed,
+        ): Self::SystemData,
+    ) {
+        for event in
+            events
+                .read(self.event_reader.as_mut().expect(
+                    "`StressViewSystem::setup` was not called before `StressViewSystem::run`",
+                ))
+                .filter(|_| !resumed.0)
+        {
+            if let InputEvent::ActionPressed(ref action) = *event {
+                if action == "toggle_stress_view" {
+                    self.enabled = !self.enabled;
+                    self.computed_at_step = None;
+                    if !self.enabled {
+                        for (e, g) in (&entities, &grabbables).join() {
+                            let highlighted = selected.contains(e) || held.contains(e);
+                            materials.insert(e, g.material(highlighted)).unwrap();
+                        }
+                    }
+                }
+            }
+        }
+        if !self.enabled || self.computed_at_step == Some(stats.steps) {
+            return;
+        }
+        self.computed_at_step = Some(stats.steps);
+        self.prepare_materials(&loader, &tex_storage, &defaults);
+        for (entity, stress) in
+            compute_stress(&physics_world, &entities, &physics_bodies, &grabbables)
+        {
+            let level = ((stress - 1.0) / (MAX_STRESS - 1.0) * (STRESS_LEVELS - 1) as f32)
+                .max(0.0)
+                .min((STRESS_LEVELS - 1) as f32) as usize;
+            materials
+                .insert(entity, self.materials[level].clone())
+                .unwrap();
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst::core::specs::prelude::SystemData;
+
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::{
+    components::{Block, Grabbable, Held, MaterialKind, Selected},
+    patterns::pattern_for,
+    resources::{Accessibility, BlockAssets, Palettes, Surface},
+};
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage},
+    renderer::{Material, MaterialDefaults, Mesh, MeshHandle, Texture},
+};
+
+use specs::Entity;
+
+/// Gives textured blocks a mesh that tiles their texture over faces of their size, again
+/// whenever they are resized, and their plain cube and color back once the texture is removed,
+/// with the pattern for the color if `Accessibility::patterns` is on.
+#[derive(Default)]
+pub struct TextureSystem {
+    /// The texture and half extents each textured block was last drawn with.
+    applied: HashMap<Entity, (String, [u32; 3])>,
+}
+
+type TextureSystemData<'s> = (
+    Entities<'s>,
+    WriteExpect<'s, BlockAssets>,
+    ReadStorage<'s, Block>,
+    ReadStorage<'s, MaterialKind>,
+    ReadStorage<'s, Selected>,
+    ReadStorage<'s, Held>,
+    WriteStorage<'s, Grabbable>,
+    WriteStorage<'s, Material>,
+    WriteStorage<'s, MeshHandle>,
+    ReadExpect<'s, Loader>,
+    Read<'s, AssetStorage<Texture>>,
+    Read<'s, AssetStorage<Mesh>>,
+    ReadExpect<'s, MaterialDefaults>,
+    Read<'s, Accessibility>,
+    Read<'s, Palettes>,
+);
+
+impl<'s> System<'s> for TextureSystem {
+    type SystemData = TextureSystemData<'s>;
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut block_assets,
+            blocks,
+            kinds,
+            selected,
+            held,
+            mut grabbables,
+            mut materials,
+            mut meshes,
+            loader,
+            tex_storage,
+            mesh_storage,
+            defaults,
+            accessibility,
+            palettes,
+        ): Self::SystemData,
+    ) {
+        self.applied.retain(|e, _| entities.is_alive(*e));
+        for (entity, block, kind) in (&entities, &blocks, &kinds).join() {
+            let highlighted = selected.contains(entity) || held.contains(entity);
+            let surface = match block.texture {
+                Some(ref name) => {
+                    let h = block.half_extents;
+                    let wanted = (name.clone(), [h.x.to_bits(), h.y.to_bits(), h.z.to_bits()]);
+                    if self.applied.get(&entity) == Some(&wanted) {
+                        continue;
+                    }
+                    self.applied.insert(entity, wanted);
+                    let mesh = block_assets.tiled_cube(&h, &loader, &mesh_storage);
+                    meshes.insert(entity, mesh).unwrap();
+                    Surface::Texture(name.clone())
+                }
+                None => {
+                    if self.applied.remove(&entity).is_none() {
+                        continue;
+                    }
+                    meshes.insert(entity, block_assets.cube()).unwrap();
+                    // `PatternSystem` may already count the block as drawn with its pattern
+                    if accessibility.patterns {
+                        Surface::pattern(block.color, pattern_for(block.color, &palettes))
+                    } else {
+                        Surface::color(block.color)
+                    }
+                }
+            };
+            let g = block_assets.grabbable(surface, *kind, &loader, &tex_storage, &defaults.0);
+            materials.insert(entity, g.material(highlighted)).unwrap();
+            grabbables.insert(entity, g).unwrap();
+        }
+    }
+}
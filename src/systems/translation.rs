@@ -2,31 +2,24 @@ use std::f32;
 
 use crate::{
     components::{CameraSelf, PhysicsBody},
-    resources::MyWorld,
+    resources::{CameraMode, MyWorld, Settings},
 };
 use amethyst::{
     controls::{HideCursor, WindowFocus},
     core::{
         nalgebra::{Unit, Vector3},
+        timing::Time,
         Transform,
     },
     ecs::{Join, Read, ReadStorage, System, Write, WriteStorage},
     input::{get_input_axis_simple, InputHandler},
     renderer::Camera,
 };
-pub struct TranslationSystem {
-    speed: f32,
-    speed_running: f32,
-}
 
-impl Default for TranslationSystem {
-    fn default() -> Self {
-        TranslationSystem {
-            speed: 1.0,
-            speed_running: 5.0,
-        }
-    }
-}
+/// Walks the player with `move_x` and `move_z` in the direction the camera looks, or flies the
+/// camera on its own in `CameraMode::FreeFly`. `CameraSystem` then puts the camera in place.
+#[derive(Default)]
+pub struct TranslationSystem;
 
 type TranslationSystemData<'s> = (
     WriteStorage<'s, Transform>,
@@ -37,6 +30,9 @@ type TranslationSystemData<'s> = (
     ReadStorage<'s, CameraSelf>,
     Write<'s, MyWorld>,
     WriteStorage<'s, PhysicsBody>,
+    Read<'s, Settings>,
+    Read<'s, CameraMode>,
+    Read<'s, Time>,
 );
 impl<'s> System<'s> for TranslationSystem {
     type SystemData = TranslationSystemData<'s>;
@@ -52,6 +48,9 @@ impl<'s> System<'s> for TranslationSystem {
             cameraself,
             mut physics_world,
             mut physics_body,
+            settings,
+            mode,
+            time,
         ): Self::SystemData,
     ) {
         let world: &mut Write<MyWorld> = &mut physics_world;
@@ -66,29 +65,31 @@ impl<'s> System<'s> for TranslationSystem {
             if focus.is_focused && hide.hide {
                 let x = get_input_axis_simple(&Some("move_x".to_owned()), &input);
                 let z = get_input_axis_simple(&Some("move_z".to_owned()), &input);
+                let speed = if input.action_is_down("run").unwrap_or(false) {
+                    settings.speed_running
+                } else {
+                    settings.speed
+                };
                 if let Some(dir) = Unit::try_new(Vector3::new(x, 0.0, z), 1.0e-6) {
                     for (transform, _) in (&mut transforms, &cameras).join() {
                         let mut iso = transform.isometry_mut();
                         let d = iso.rotation * dir.as_ref();
-                        if let Some(d) = Unit::try_new(Vector3::new(d.x, 0.0, d.z), 1.0e-6) {
-                            let linear = Vector3::new(d.x, 0.0, d.z);
-                            let h = if let Some(co) = world.collider(body.0) {
-                                co.shape().aabb(co.position()).maxs().y
-                            } else {
-                                0.0
-                            };
-                            if let Some(rb) = world
-                                .collider_body_handle(body.0)
-                                .and_then(|bh| world.rigid_body_mut(bh))
-                            {
-                                let speed = if input.key_is_down(winit::VirtualKeyCode::LShift) {
-                                    self.speed_running
-                                } else {
-                                    self.speed
-                                };
-                                rb.set_linear_velocity(linear * speed);
-                                let pos = rb.position().translation.vector;
-                                iso.translation.vector = Vector3::new(pos.x, h, pos.z);
+                        match *mode {
+                            CameraMode::FreeFly => {
+                                iso.translation.vector += d * speed * time.delta_seconds();
+                            }
+                            CameraMode::Orbit => (),
+                            CameraMode::FirstPerson | CameraMode::ThirdPerson => {
+                                if let Some(d) = Unit::try_new(Vector3::new(d.x, 0.0, d.z), 1.0e-6)
+                                {
+                                    let linear = Vector3::new(d.x, 0.0, d.z);
+                                    if let Some(rb) = world
+                                        .collider_body_handle(body.0)
+                                        .and_then(|bh| world.rigid_body_mut(bh))
+                                    {
+                                        rb.set_linear_velocity(linear * speed);
+                                    }
+                                }
                             }
                         }
                     }